@@ -0,0 +1,51 @@
+use std::io::{self, Write};
+
+use crate::{
+    cli::OutputFormat,
+    db::{
+        lexer::split_statements,
+        sql_client::{QueryResult, SqlClient},
+    },
+    export::{to_csv, to_json, to_text_table},
+};
+
+/// Runs `sql` without the screen, printing the rows of each statement to stdout in `format`
+/// and what the other statements did to stderr, like `mysql -e` does. Stops at the first
+/// error. Returns whether every statement succeeded.
+pub async fn run_batch(sql_client: &dyn SqlClient, sql: &str, format: OutputFormat) -> bool {
+    let statements = split_statements(sql, sql_client.dialect());
+    let results = sql_client.execute_script(&statements, false).await;
+    let mut stdout = io::stdout();
+    for statement_result in results.iter() {
+        match &statement_result.result {
+            Ok(QueryResult::Rows(headers, records)) => {
+                let output = match format {
+                    OutputFormat::Table if headers.is_empty() => String::new(),
+                    OutputFormat::Table => to_text_table(headers, records),
+                    OutputFormat::Csv => to_csv(headers, records),
+                    OutputFormat::Json => to_json(headers, records),
+                };
+                // A closed pipe, e.g. into `head`, is not an error of the SQL
+                if stdout.write_all(output.as_bytes()).is_err() {
+                    return true;
+                }
+            }
+            Ok(QueryResult::Summary {
+                rows_affected,
+                last_insert_id,
+            }) => {
+                let mut summary = format!("Query OK, {} rows affected", rows_affected);
+                if let Some(last_insert_id) = last_insert_id {
+                    summary.push_str(&format!(", last insert id {}", last_insert_id));
+                }
+                eprintln!("{}", summary);
+            }
+            Err(error) => {
+                eprintln!("ERROR: {}\n  in: {}", error, statement_result.sql);
+                return false;
+            }
+        }
+    }
+    let _ = stdout.flush();
+    return true;
+}
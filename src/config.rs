@@ -0,0 +1,187 @@
+use std::{
+    env, fs, io,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+use anyhow::Context;
+use serde::Deserialize;
+use url::Url;
+
+/// The named connections of the config file, in the order they are listed there.
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+    #[serde(default, rename = "connection")]
+    pub connections: Vec<ConnectionProfile>,
+}
+
+/// One `[[connection]]` of the config file: either a `url`, or a `driver` with host, port
+/// and user. The password never comes from the file itself, but from `password_env`,
+/// the output of `password_command` or a prompt if `password_prompt` is set.
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConnectionProfile {
+    pub name: String,
+    pub url: Option<String>,
+    pub driver: Option<String>,
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub user: Option<String>,
+    /// The database opened after connecting, instead of the first one listed.
+    pub database: Option<String>,
+    /// Opens every session read-only and keeps the screens that edit, insert, delete and
+    /// import rows closed. Only the session is read-only though: SQL run from the editor can
+    /// turn it back with e.g. `SET SESSION TRANSACTION READ WRITE` on MySQL and Postgres, so
+    /// give the profile a user without write privileges where that matters.
+    #[serde(default)]
+    pub read_only: bool,
+    /// Name of the colour the connection is tagged with on screen, e.g. `red` for production.
+    pub color: Option<String>,
+    pub password_env: Option<String>,
+    pub password_command: Option<String>,
+    #[serde(default)]
+    pub password_prompt: bool,
+    // Only read to refuse it with a better message than an unknown field
+    #[serde(default)]
+    password: Option<toml::Value>,
+}
+
+impl Config {
+    /// Reads the config file, which is optional.
+    /// `CONFIG_FILE` overrides the default location in the user's config directory.
+    pub fn load() -> anyhow::Result<Self> {
+        let path = env::var("CONFIG_FILE")
+            .ok()
+            .map(PathBuf::from)
+            .or_else(|| dirs::config_dir().map(|dir| dir.join("sql-cli-rust").join("config.toml")));
+        let content = match path.as_ref().map(fs::read_to_string) {
+            Some(Ok(content)) => content,
+            Some(Err(e)) if e.kind() != io::ErrorKind::NotFound => {
+                return Err(e).context(format!("{}", path.unwrap().display()));
+            }
+            _ => {
+                return Ok(Self {
+                    path: path,
+                    connections: vec![],
+                })
+            }
+        };
+        let mut config: Config = toml::from_str(&content)
+            .with_context(|| format!("{}", path.as_ref().unwrap().display()))?;
+        for profile in config.connections.iter() {
+            profile.check()?;
+        }
+        config.path = path;
+        return Ok(config);
+    }
+}
+
+impl ConnectionProfile {
+    fn check(&self) -> anyhow::Result<()> {
+        let stored_password = self.password.is_some()
+            || self
+                .url
+                .as_ref()
+                .and_then(|url| Url::parse(url).ok())
+                .is_some_and(|url| url.password().is_some());
+        if stored_password {
+            anyhow::bail!(
+                "Connection {} stores its password in plain text, use password_env, password_command or password_prompt instead",
+                self.name
+            );
+        }
+        let password_sources = [
+            self.password_env.is_some(),
+            self.password_command.is_some(),
+            self.password_prompt,
+        ];
+        if password_sources.iter().filter(|source| **source).count() > 1 {
+            anyhow::bail!(
+                "Connection {} sets more than one of password_env, password_command and password_prompt",
+                self.name
+            );
+        }
+        if self.url.is_none() && self.driver.is_none() {
+            anyhow::bail!("Connection {} needs a url or a driver", self.name);
+        }
+        return Ok(());
+    }
+
+    /// The password from `password_env` or `password_command`, if the profile has one of them.
+    /// Asking for the password when `password_prompt` is set is up to the caller.
+    pub fn password(&self) -> anyhow::Result<Option<String>> {
+        if let Some(var) = self.password_env.as_ref() {
+            let password = env::var(var).with_context(|| {
+                format!(
+                    "Connection {} reads its password from {}, which is not set",
+                    self.name, var
+                )
+            })?;
+            return Ok(Some(password));
+        }
+        if let Some(command) = self.password_command.as_ref() {
+            let output = Command::new("sh")
+                .arg("-c")
+                .arg(command)
+                .stdin(Stdio::null())
+                .output()
+                .with_context(|| format!("Could not run the password command of {}", self.name))?;
+            if !output.status.success() {
+                anyhow::bail!(
+                    "The password command of {} exited with {}: {}",
+                    self.name,
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            let password = String::from_utf8(output.stdout).with_context(|| {
+                format!("The password command of {} printed no text", self.name)
+            })?;
+            return Ok(Some(password.trim_end_matches(['\r', '\n']).to_string()));
+        }
+        return Ok(None);
+    }
+
+    /// The URL to connect to, with `password` filled in if there is one.
+    pub fn url(&self, password: Option<&str>) -> anyhow::Result<String> {
+        let url = match (self.url.as_ref(), self.driver.as_deref()) {
+            (Some(url), _) => url.clone(),
+            (None, Some(driver @ ("mysql" | "postgres"))) => {
+                let mut url = Url::parse(&format!(
+                    "{}://{}",
+                    driver,
+                    self.host.as_deref().unwrap_or("localhost")
+                ))
+                .with_context(|| format!("Connection {} has an invalid host", self.name))?;
+                // Only fails for URLs without a host, which this has
+                let _ = url.set_port(self.port);
+                if let Some(user) = self.user.as_ref() {
+                    let _ = url.set_username(user);
+                }
+                url.to_string()
+            }
+            (None, Some(driver)) => anyhow::bail!(
+                "Connection {} has driver {}, expected mysql or postgres (SQLite takes a url)",
+                self.name,
+                driver
+            ),
+            (None, None) => anyhow::bail!("Connection {} needs a url or a driver", self.name),
+        };
+        let password = match password {
+            Some(password) => password,
+            None => return Ok(url),
+        };
+        let mut url = Url::parse(&url)
+            .with_context(|| format!("Connection {} has an invalid url", self.name))?;
+        if url.set_password(Some(password)).is_err() {
+            anyhow::bail!(
+                "Connection {} has no server to send a password to",
+                self.name
+            );
+        }
+        return Ok(url.to_string());
+    }
+}
@@ -0,0 +1,549 @@
+/// Words highlighted as keywords and offered by completion, in upper case.
+pub const KEYWORDS: &[&str] = &[
+    "ADD",
+    "ALL",
+    "ALTER",
+    "AND",
+    "AS",
+    "ASC",
+    "AUTO_INCREMENT",
+    "BEGIN",
+    "BETWEEN",
+    "BY",
+    "CASE",
+    "CHECK",
+    "COLUMN",
+    "COMMIT",
+    "CONSTRAINT",
+    "CREATE",
+    "CROSS",
+    "DATABASE",
+    "DEFAULT",
+    "DELETE",
+    "DESC",
+    "DESCRIBE",
+    "DISTINCT",
+    "DROP",
+    "ELSE",
+    "END",
+    "EXISTS",
+    "EXPLAIN",
+    "FALSE",
+    "FOREIGN",
+    "FROM",
+    "FULL",
+    "GROUP",
+    "HAVING",
+    "IF",
+    "IN",
+    "INDEX",
+    "INNER",
+    "INSERT",
+    "INTERVAL",
+    "INTO",
+    "IS",
+    "JOIN",
+    "KEY",
+    "LEFT",
+    "LIKE",
+    "LIMIT",
+    "NOT",
+    "NULL",
+    "OFFSET",
+    "ON",
+    "OR",
+    "ORDER",
+    "OUTER",
+    "PRIMARY",
+    "REFERENCES",
+    "RENAME",
+    "REPLACE",
+    "RETURNING",
+    "RIGHT",
+    "ROLLBACK",
+    "SELECT",
+    "SET",
+    "SHOW",
+    "TABLE",
+    "TABLES",
+    "THEN",
+    "TO",
+    "TRUE",
+    "TRUNCATE",
+    "UNION",
+    "UNIQUE",
+    "UPDATE",
+    "USE",
+    "USING",
+    "VALUES",
+    "VIEW",
+    "WHEN",
+    "WHERE",
+    "WITH",
+];
+
+/// Operators made of more than one character, longest first.
+const OPERATORS: &[&str] = &[
+    "->>", "<=>", "<=", ">=", "<>", "!=", "||", "&&", "::", ":=", "->", "<<", ">>",
+];
+
+/// The SQL flavour being read, which decides what quotes and comments look like.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum Dialect {
+    /// Backslash escapes in strings and `#` comments.
+    #[default]
+    MySql,
+    /// `$tag$ ... $tag$` quoting, and backslash escapes only in `E'...'` strings.
+    Postgres,
+    Sqlite,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TokenKind {
+    Keyword,
+    Identifier,
+    /// A `'single'` quoted literal, a MySQL `"double"` quoted one, or a Postgres
+    /// `$$dollar quoted$$` one. Elsewhere `"double"` quotes an identifier.
+    String,
+    Number,
+    /// A `-- line`, `# line` or `/* block */` comment.
+    Comment,
+    /// Operators and punctuation, one token per symbol.
+    Operator,
+    Whitespace,
+}
+
+/// A piece of SQL, located by byte offsets into the tokenized text.
+#[derive(Clone, Copy, Debug)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub start: usize,
+    pub end: usize,
+    /// False for a string, quoted identifier or block comment that runs to the end of the text.
+    pub terminated: bool,
+}
+
+impl Token {
+    pub fn text<'s>(&self, sql: &'s str) -> &'s str {
+        return &sql[self.start..self.end];
+    }
+}
+
+/// Splits `sql` into tokens that cover it completely, so joining their text gives `sql` back.
+pub fn tokenize(sql: &str, dialect: Dialect) -> Vec<Token> {
+    let chars: Vec<(usize, char)> = sql.char_indices().collect();
+    let byte_at = |i: usize| chars.get(i).map(|(byte, _)| *byte).unwrap_or(sql.len());
+    let char_at = |i: usize| chars.get(i).map(|(_, c)| *c);
+
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i].1;
+        let start = i;
+        let mut terminated = true;
+        let kind = if c.is_whitespace() {
+            while char_at(i).is_some_and(char::is_whitespace) {
+                i += 1;
+            }
+            TokenKind::Whitespace
+        } else if (c == '-' && char_at(i + 1) == Some('-'))
+            || (c == '#' && dialect == Dialect::MySql)
+        {
+            while char_at(i).is_some_and(|c| c != '\n') {
+                i += 1;
+            }
+            TokenKind::Comment
+        } else if c == '/' && char_at(i + 1) == Some('*') {
+            i += 2;
+            terminated = false;
+            while i < chars.len() {
+                if chars[i].1 == '*' && char_at(i + 1) == Some('/') {
+                    i += 2;
+                    terminated = true;
+                    break;
+                }
+                i += 1;
+            }
+            TokenKind::Comment
+        } else if let Some(tag_length) = dollar_quote_tag(&chars, i, dialect) {
+            let tag: String = chars[i..i + tag_length].iter().map(|(_, c)| *c).collect();
+            let body = &sql[byte_at(i + tag_length)..];
+            i = match body.find(&tag) {
+                Some(offset) => i + tag_length + body[..offset].chars().count() + tag_length,
+                None => {
+                    terminated = false;
+                    chars.len()
+                }
+            };
+            TokenKind::String
+        } else if c == '\'' || c == '"' || c == '`' || is_escape_string_start(&chars, i, dialect) {
+            // Only MySQL strings and Postgres' `E'...'` strings take backslash escapes
+            let escapes = match dialect {
+                Dialect::MySql => c != '`',
+                _ => c == 'e' || c == 'E',
+            };
+            if c == 'e' || c == 'E' {
+                i += 1;
+            }
+            let quote = chars[i].1;
+            i += 1;
+            terminated = false;
+            while let Some(next) = char_at(i) {
+                i += 1;
+                if next == '\\' && escapes {
+                    i += 1;
+                } else if next == quote {
+                    // A doubled quote is an escaped quote, not the end.
+                    if char_at(i) == Some(quote) {
+                        i += 1;
+                    } else {
+                        terminated = true;
+                        break;
+                    }
+                }
+            }
+            i = i.min(chars.len());
+            // ANSI_QUOTES, which makes MySQL read them as identifiers too, is not the default
+            if quote == '`' || (quote == '"' && dialect != Dialect::MySql) {
+                TokenKind::Identifier
+            } else {
+                TokenKind::String
+            }
+        } else if c.is_ascii_digit()
+            || (c == '.' && char_at(i + 1).is_some_and(|c| c.is_ascii_digit()))
+        {
+            i = scan_number(&chars, i);
+            TokenKind::Number
+        } else if c.is_alphabetic() || c == '_' {
+            while char_at(i).is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '$') {
+                i += 1;
+            }
+            let word = &sql[byte_at(start)..byte_at(i)];
+            if is_keyword(word) {
+                TokenKind::Keyword
+            } else {
+                TokenKind::Identifier
+            }
+        } else {
+            let rest = &sql[byte_at(i)..];
+            i += OPERATORS
+                .iter()
+                .find(|operator| rest.starts_with(*operator))
+                .map_or(1, |operator| operator.len());
+            TokenKind::Operator
+        };
+        tokens.push(Token {
+            kind: kind,
+            start: byte_at(start),
+            end: byte_at(i),
+            terminated: terminated,
+        });
+    }
+    return tokens;
+}
+
+pub fn is_keyword(word: &str) -> bool {
+    let upper = word.to_uppercase();
+    return KEYWORDS.iter().any(|keyword| *keyword == upper);
+}
+
+/// Indexes into `tokens` of the parentheses that have no partner.
+pub fn unbalanced_parens(sql: &str, tokens: &[Token]) -> Vec<usize> {
+    let mut open = vec![];
+    let mut unbalanced = vec![];
+    for (index, token) in tokens.iter().enumerate() {
+        if token.kind != TokenKind::Operator {
+            continue;
+        }
+        match token.text(sql) {
+            "(" => open.push(index),
+            ")" if open.pop().is_none() => unbalanced.push(index),
+            _ => {}
+        }
+    }
+    unbalanced.extend(open);
+    unbalanced.sort_unstable();
+    return unbalanced;
+}
+
+/// Splits a script into statements on `;`, or on the delimiter set by a MySQL client style
+/// `DELIMITER` line, ignoring delimiters inside quotes and comments.
+/// Statements are trimmed and those with nothing but comments are dropped.
+pub fn split_statements(sql: &str, dialect: Dialect) -> Vec<String> {
+    let tokens = tokenize(sql, dialect);
+    let mut statements = vec![];
+    let mut delimiter = String::from(";");
+    let mut start = 0;
+    let mut has_code = false;
+    let mut index = 0;
+    while index < tokens.len() {
+        let token = tokens[index];
+        let text = token.text(sql);
+        let mut end = None;
+        if !has_code && text.eq_ignore_ascii_case("DELIMITER") {
+            // The rest of the line is the new delimiter; the line itself is not sent.
+            let line_end = sql[token.end..]
+                .find('\n')
+                .map_or(sql.len(), |offset| token.end + offset);
+            let new_delimiter = sql[token.end..line_end].trim();
+            if !new_delimiter.is_empty() {
+                delimiter = new_delimiter.to_string();
+            }
+            start = line_end;
+            end = Some(line_end);
+        } else if !matches!(
+            token.kind,
+            TokenKind::String | TokenKind::Comment | TokenKind::Whitespace
+        ) && sql[token.start..].starts_with(&delimiter)
+        {
+            if has_code {
+                statements.push(sql[start..token.start].trim().to_string());
+            }
+            start = token.start + delimiter.len();
+            has_code = false;
+            end = Some(start);
+        } else if !matches!(token.kind, TokenKind::Comment | TokenKind::Whitespace) {
+            has_code = true;
+        }
+
+        match end {
+            // Skip the tokens the delimiter or DELIMITER line covered
+            Some(end) => {
+                while index < tokens.len() && tokens[index].start < end {
+                    index += 1;
+                }
+            }
+            None => index += 1,
+        }
+    }
+    if has_code {
+        statements.push(sql[start..].trim().to_string());
+    }
+    return statements;
+}
+
+/// Tables named after FROM, JOIN, UPDATE and INTO, without quotes or schema prefix.
+/// Comma-separated FROM lists and aliases are understood.
+pub fn referenced_tables(sql: &str, dialect: Dialect) -> Vec<String> {
+    let mut tables: Vec<String> = vec![];
+    let mut expect_table = false;
+    let mut after_table = false;
+    let mut qualified = false;
+    let tokens = tokenize(sql, dialect);
+    let significant = tokens
+        .iter()
+        .filter(|token| !matches!(token.kind, TokenKind::Whitespace | TokenKind::Comment));
+    for token in significant {
+        let text = token.text(sql);
+        match token.kind {
+            TokenKind::Identifier if expect_table || qualified => {
+                let name = unquote_identifier(text);
+                if qualified {
+                    tables.pop();
+                }
+                tables.push(name);
+                expect_table = false;
+                after_table = true;
+                qualified = false;
+            }
+            TokenKind::Identifier if after_table => {}
+            TokenKind::Keyword => {
+                expect_table = matches!(
+                    text.to_uppercase().as_str(),
+                    "FROM" | "JOIN" | "UPDATE" | "INTO"
+                );
+                after_table = after_table && text.eq_ignore_ascii_case("AS");
+            }
+            TokenKind::Operator if after_table && text == "," => {
+                expect_table = true;
+                after_table = false;
+            }
+            TokenKind::Operator if after_table && text == "." => {
+                qualified = true;
+            }
+            _ => {
+                expect_table = false;
+                after_table = false;
+            }
+        }
+    }
+    tables.dedup();
+    return tables;
+}
+
+/// `text` without the quotes around it, and with the quotes doubled inside undoubled.
+fn unquote_identifier(text: &str) -> String {
+    let quote = match text.chars().next() {
+        Some(quote @ ('`' | '"')) => quote,
+        _ => return text.to_string(),
+    };
+    let inner = &text[1..];
+    return inner
+        .strip_suffix(quote)
+        .unwrap_or(inner)
+        .replace(&format!("{0}{0}", quote), &quote.to_string());
+}
+
+/// Length of the `$tag$` opening a Postgres dollar quoted string at `i`, if one does.
+/// The tag is empty or an identifier that does not start with a digit, unlike `$1`.
+fn dollar_quote_tag(chars: &[(usize, char)], i: usize, dialect: Dialect) -> Option<usize> {
+    if dialect != Dialect::Postgres || chars[i].1 != '$' {
+        return None;
+    }
+    let mut end = i + 1;
+    while let Some((_, c)) = chars.get(end) {
+        match c {
+            '$' => return Some(end + 1 - i),
+            c if c.is_alphabetic() || *c == '_' => end += 1,
+            c if c.is_ascii_digit() && end > i + 1 => end += 1,
+            _ => return None,
+        }
+    }
+    return None;
+}
+
+/// Whether a Postgres `E'...'` string starts at `i`.
+fn is_escape_string_start(chars: &[(usize, char)], i: usize, dialect: Dialect) -> bool {
+    return dialect == Dialect::Postgres
+        && matches!(chars[i].1, 'e' | 'E')
+        && chars.get(i + 1).is_some_and(|(_, c)| *c == '\'')
+        && (i == 0 || !is_word_char(chars[i - 1].1));
+}
+
+fn is_word_char(c: char) -> bool {
+    return c.is_alphanumeric() || c == '_' || c == '$';
+}
+
+/// Index just past the number starting at `i`: digits, a fraction, an exponent or a `0x` hex literal.
+fn scan_number(chars: &[(usize, char)], mut i: usize) -> usize {
+    let char_at = |i: usize| chars.get(i).map(|(_, c)| *c);
+    if char_at(i) == Some('0') && matches!(char_at(i + 1), Some('x') | Some('X')) {
+        i += 2;
+        while char_at(i).is_some_and(|c| c.is_ascii_hexdigit()) {
+            i += 1;
+        }
+        return i;
+    }
+    while char_at(i).is_some_and(|c| c.is_ascii_digit()) {
+        i += 1;
+    }
+    if char_at(i) == Some('.') {
+        i += 1;
+        while char_at(i).is_some_and(|c| c.is_ascii_digit()) {
+            i += 1;
+        }
+    }
+    if matches!(char_at(i), Some('e') | Some('E')) {
+        let mut j = i + 1;
+        if matches!(char_at(j), Some('+') | Some('-')) {
+            j += 1;
+        }
+        if char_at(j).is_some_and(|c| c.is_ascii_digit()) {
+            i = j;
+            while char_at(i).is_some_and(|c| c.is_ascii_digit()) {
+                i += 1;
+            }
+        }
+    }
+    return i;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{referenced_tables, split_statements, Dialect};
+
+    #[test]
+    fn splits_on_semicolons_outside_quotes_and_comments() {
+        let sql = "SELECT ';'; -- a; comment\nSELECT \"x;y\"; /* ; */ SELECT 3";
+        assert_eq!(
+            split_statements(sql, Dialect::MySql),
+            vec![
+                "SELECT ';'",
+                "-- a; comment\nSELECT \"x;y\"",
+                "/* ; */ SELECT 3"
+            ]
+        );
+    }
+
+    #[test]
+    fn drops_statements_with_only_comments() {
+        assert_eq!(
+            split_statements("SELECT 1;\n-- done\n;", Dialect::Sqlite),
+            vec!["SELECT 1"]
+        );
+    }
+
+    #[test]
+    fn follows_delimiter_lines() {
+        let sql =
+            "DELIMITER //\nCREATE PROCEDURE p() BEGIN SELECT 1; END//\nDELIMITER ;\nCALL p();";
+        assert_eq!(
+            split_statements(sql, Dialect::MySql),
+            vec!["CREATE PROCEDURE p() BEGIN SELECT 1; END", "CALL p()"]
+        );
+    }
+
+    #[test]
+    fn reads_backslash_escapes_only_in_mysql() {
+        let sql = "SELECT 'a\\'; SELECT 2";
+        assert_eq!(split_statements(sql, Dialect::MySql).len(), 1);
+        assert_eq!(
+            split_statements(sql, Dialect::Postgres),
+            vec!["SELECT 'a\\'", "SELECT 2"]
+        );
+        assert_eq!(split_statements(sql, Dialect::Sqlite).len(), 2);
+        assert_eq!(
+            split_statements("SELECT E'a\\'; b'; SELECT 2", Dialect::Postgres).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn reads_hash_comments_only_in_mysql() {
+        let sql = "SELECT 1 # x;\n; SELECT 2";
+        assert_eq!(split_statements(sql, Dialect::MySql).len(), 2);
+        assert_eq!(
+            split_statements("SELECT '{}'::jsonb #> '{a}'; SELECT 2", Dialect::Postgres).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn keeps_dollar_quoted_bodies_whole_in_postgres() {
+        let sql = "CREATE FUNCTION f() RETURNS int AS $$ SELECT 1; $$ LANGUAGE sql;\n\
+                   DO $body$ BEGIN PERFORM 1; END $body$; SELECT $1";
+        assert_eq!(
+            split_statements(sql, Dialect::Postgres),
+            vec![
+                "CREATE FUNCTION f() RETURNS int AS $$ SELECT 1; $$ LANGUAGE sql",
+                "DO $body$ BEGIN PERFORM 1; END $body$",
+                "SELECT $1"
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_referenced_tables() {
+        assert_eq!(
+            referenced_tables(
+                "SELECT * FROM users u, `orders` JOIN app.items ON 1",
+                Dialect::MySql
+            ),
+            vec!["users", "orders", "items"]
+        );
+    }
+
+    #[test]
+    fn reads_double_quotes_as_identifiers_outside_mysql() {
+        let sql = r#"SELECT * FROM "Orders" o JOIN "odd ""name""" ON 1"#;
+        assert_eq!(
+            referenced_tables(sql, Dialect::Postgres),
+            vec!["Orders", "odd \"name\""]
+        );
+        assert_eq!(
+            referenced_tables(r#"SELECT * FROM "Orders""#, Dialect::Sqlite),
+            vec!["Orders"]
+        );
+        assert!(referenced_tables(r#"SELECT * FROM "Orders""#, Dialect::MySql).is_empty());
+    }
+}
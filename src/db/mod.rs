@@ -1,2 +1,8 @@
+pub mod lexer;
+pub mod mysql_client;
+pub mod option_file;
 pub mod parser;
+pub mod postgres_client;
 pub mod sql_client;
+pub mod sqlite_client;
+pub mod statement;
@@ -0,0 +1,422 @@
+use std::{str::FromStr, sync::RwLock, time::Instant};
+
+use async_trait::async_trait;
+use futures_util::TryStreamExt;
+use sqlx::{
+    mysql::{MySqlArguments, MySqlConnectOptions, MySqlPoolOptions},
+    query::Query,
+    Connection, Either, Executor, MySql, Pool, Transaction,
+};
+
+use tokio::sync::Mutex;
+use url::Url;
+
+use crate::{
+    model::{cell::CellValue, table::RecordSort},
+    utils::quote_identifier,
+};
+
+use super::{
+    lexer::Dialect,
+    option_file::ClientOptions,
+    parser::{is_row_returning_query, parse_sql_db, parse_sql_table_rows, parse_sql_tables},
+    sql_client::{
+        no_transaction_error, order_by_clause, script_error, where_clause, QueryResult,
+        RunningStatement, ScriptProgress, SqlClient, StatementResult,
+    },
+    statement::{literal, non_finite_literal, Statement},
+};
+
+pub struct MySqlClient {
+    options: MySqlConnectOptions,
+    /// The database named by the URL or an option file, to open first.
+    default_database: Option<String>,
+    read_only: bool,
+    // Swapped for a pool on the new database by `reconnect`.
+    pool: RwLock<Pool<MySql>>,
+    progress: ScriptProgress,
+    // Connections are not `Sync`, so the open transaction sits behind a mutex.
+    transaction: Mutex<Option<Transaction<'static, MySql>>>,
+}
+
+impl MySqlClient {
+    /// Connects to `base_db_url`, taking what it leaves out from the `[client]` section of
+    /// the MySQL option files: host, port, user, password, socket and database.
+    pub async fn new(base_db_url: String, read_only: bool) -> anyhow::Result<Self> {
+        let url = Url::parse(&base_db_url)?;
+        let client_options = ClientOptions::load()?;
+        let mut options = MySqlConnectOptions::from_str(&base_db_url)?;
+        let host = match (url.host_str().unwrap_or_default(), client_options.host) {
+            ("", Some(host)) => host,
+            ("", None) => "localhost".to_string(),
+            (host, _) => host.to_string(),
+        };
+        options = options.host(&host);
+        if let (None, Some(port)) = (url.port(), client_options.port) {
+            options = options.port(port);
+        }
+        if let ("", Some(user)) = (url.username(), client_options.user) {
+            options = options.username(&user);
+        }
+        if let (None, Some(password)) = (url.password(), client_options.password) {
+            options = options.password(&password);
+        }
+        if let Some(socket) = option_file_socket(&url, &host, client_options.socket) {
+            options = options.socket(socket);
+        }
+        let default_database = match url.path().trim_start_matches('/') {
+            "" => client_options.database,
+            database => Some(database.to_string()),
+        };
+
+        Ok(Self {
+            pool: RwLock::new(connect_pool(options.clone(), read_only).await?),
+            options: options,
+            default_database: default_database,
+            read_only: read_only,
+            progress: ScriptProgress::new(),
+            transaction: Mutex::new(None),
+        })
+    }
+
+    fn pool(&self) -> Pool<MySql> {
+        return self.pool.read().unwrap().clone();
+    }
+}
+
+#[async_trait]
+impl SqlClient for MySqlClient {
+    async fn reconnect(&self, new_database: String) -> anyhow::Result<()> {
+        let options = self.options.clone().database(&new_database);
+        let pool = connect_pool(options, self.read_only).await?;
+        *self.pool.write().unwrap() = pool;
+        Ok(())
+    }
+
+    fn default_database(&self) -> Option<String> {
+        return self.default_database.clone();
+    }
+
+    async fn ping(&self) -> anyhow::Result<()> {
+        self.pool().acquire().await?.ping().await?;
+        Ok(())
+    }
+
+    async fn get_database_list(&self) -> anyhow::Result<Vec<String>> {
+        let get_db_query = "SHOW DATABASES".to_string();
+        let db_rows = sqlx::query(get_db_query.as_str())
+            .fetch_all(&self.pool())
+            .await?;
+
+        return Ok(parse_sql_db(db_rows));
+    }
+
+    async fn get_table_list(&self, db_name: String) -> anyhow::Result<Vec<String>> {
+        let get_tables_query = format!(
+            "{} {}",
+            "SHOW TABLE STATUS FROM",
+            self.quote_identifier(&db_name)
+        );
+        let table_rows = sqlx::query(get_tables_query.as_str())
+            .fetch_all(&self.pool())
+            .await?;
+
+        return Ok(parse_sql_tables(table_rows));
+    }
+
+    async fn get_table_records(
+        &self,
+        table_name: String,
+        filter: Option<&str>,
+        sort: Option<&RecordSort>,
+        key_columns: &[String],
+        limit: usize,
+        offset: usize,
+    ) -> Result<(Vec<String>, Vec<Vec<CellValue>>), sqlx::Error> {
+        let get_records_query = format!(
+            "{} {} {} {} LIMIT {} OFFSET {}",
+            "SELECT * FROM",
+            self.quote_table_name(&table_name),
+            where_clause(filter),
+            order_by_clause(sort, key_columns, '`'),
+            limit,
+            offset
+        );
+        let record_rows = sqlx::query(get_records_query.as_str())
+            .fetch_all(&self.pool())
+            .await?;
+
+        return Ok(parse_sql_table_rows(record_rows));
+    }
+
+    async fn get_table_record_count(
+        &self,
+        table_name: String,
+        filter: Option<&str>,
+    ) -> Result<u64, sqlx::Error> {
+        let get_count_query = format!(
+            "{} {} {}",
+            "SELECT COUNT(*) FROM",
+            self.quote_table_name(&table_name),
+            where_clause(filter)
+        );
+        let count: i64 = sqlx::query_scalar(get_count_query.as_str())
+            .fetch_one(&self.pool())
+            .await?;
+
+        return Ok(count as u64);
+    }
+
+    async fn get_table_columns(
+        &self,
+        table_name: String,
+    ) -> anyhow::Result<(Vec<String>, Vec<Vec<CellValue>>)> {
+        let get_records_query = format!(
+            "{} {}",
+            "SHOW COLUMNS FROM",
+            self.quote_table_name(&table_name)
+        );
+        let column_rows = sqlx::query(get_records_query.as_str())
+            .fetch_all(&self.pool())
+            .await?;
+
+        return Ok(parse_sql_table_rows(column_rows));
+    }
+
+    async fn execute_script(
+        &self,
+        statements: &[String],
+        continue_on_error: bool,
+    ) -> Vec<StatementResult> {
+        let _script = self.progress.begin();
+        let mut connection = match self.pool().acquire().await {
+            Ok(connection) => connection,
+            Err(e) => return script_error(statements, e),
+        };
+        let connection_id = sqlx::query_scalar::<_, u64>("SELECT CONNECTION_ID()")
+            .fetch_one(&mut *connection)
+            .await
+            .ok()
+            .map(|id| id as i64);
+        let mut results = vec![];
+        for (index, sql) in statements.iter().enumerate() {
+            if !self.progress.start(connection_id, index) {
+                break;
+            }
+            let started = Instant::now();
+            let result = run_input_query(&mut *connection, sql).await;
+            let failed = result.is_err();
+            results.push(StatementResult {
+                sql: sql.clone(),
+                result: result,
+                duration: started.elapsed(),
+            });
+            if failed && !continue_on_error {
+                break;
+            }
+        }
+        return results;
+    }
+
+    fn running_statement(&self) -> Option<RunningStatement> {
+        return self.progress.running();
+    }
+
+    async fn cancel_query(&self) -> Result<bool, sqlx::Error> {
+        let running = match self.progress.cancel() {
+            Some(running) => running,
+            None => return Ok(true),
+        };
+        if let Some(connection_id) = running.connection_id {
+            // Goes to another connection of the pool, the script's own is checked out
+            sqlx::query(&format!("KILL QUERY {}", connection_id))
+                .execute(&self.pool())
+                .await?;
+        }
+        Ok(true)
+    }
+
+    async fn execute_transaction(&self, statements: &[Statement]) -> Result<u64, sqlx::Error> {
+        let mut transaction = self.pool().begin().await?;
+        let mut rows_affected = 0;
+        for statement in statements.iter() {
+            let mut query = sqlx::query(statement.sql.as_str());
+            for param in statement.params.iter() {
+                query = bind_value(query, param);
+            }
+            rows_affected += query.execute(&mut transaction).await?.rows_affected();
+        }
+        transaction.commit().await?;
+        return Ok(rows_affected);
+    }
+
+    async fn begin_transaction(&self) -> Result<(), sqlx::Error> {
+        *self.transaction.lock().await = Some(self.pool().begin().await?);
+        Ok(())
+    }
+
+    async fn execute_in_transaction(&self, statement: &Statement) -> Result<u64, sqlx::Error> {
+        let mut transaction = self.transaction.lock().await;
+        let transaction = transaction.as_mut().ok_or_else(no_transaction_error)?;
+        let mut query = sqlx::query(statement.sql.as_str());
+        for param in statement.params.iter() {
+            query = bind_value(query, param);
+        }
+        let result = query.execute(transaction).await?;
+        return Ok(result.rows_affected());
+    }
+
+    async fn commit_transaction(&self) -> Result<(), sqlx::Error> {
+        let transaction = self
+            .transaction
+            .lock()
+            .await
+            .take()
+            .ok_or_else(no_transaction_error)?;
+        return transaction.commit().await;
+    }
+
+    async fn rollback_transaction(&self) -> Result<(), sqlx::Error> {
+        let transaction = self
+            .transaction
+            .lock()
+            .await
+            .take()
+            .ok_or_else(no_transaction_error)?;
+        return transaction.rollback().await;
+    }
+
+    fn dialect(&self) -> Dialect {
+        return Dialect::MySql;
+    }
+
+    fn quote_identifier(&self, identifier: &str) -> String {
+        return quote_identifier(identifier, '`');
+    }
+
+    fn quote_table_name(&self, table_name: &str) -> String {
+        return quote_identifier(table_name, '`');
+    }
+
+    fn quote_literal(&self, value: &CellValue) -> String {
+        return non_finite_literal(value, Dialect::MySql).unwrap_or_else(|| literal(value, true));
+    }
+
+    fn placeholder(&self, _index: usize, _column_type: &str) -> String {
+        return String::from("?");
+    }
+}
+
+/// Opens a pool with `options` whose sessions refuse to write if `read_only` is set.
+/// The option file socket to connect through. Like the mysql client, it only replaces TCP
+/// for `localhost`, and a socket named by the URL wins.
+fn option_file_socket(url: &Url, host: &str, socket: Option<String>) -> Option<String> {
+    if host != "localhost" || url.query_pairs().any(|(key, _)| key == "socket") {
+        return None;
+    }
+    return socket;
+}
+
+async fn connect_pool(
+    options: MySqlConnectOptions,
+    read_only: bool,
+) -> Result<Pool<MySql>, sqlx::Error> {
+    let mut pool_options = MySqlPoolOptions::new();
+    if read_only {
+        pool_options = pool_options.after_connect(|connection| {
+            Box::pin(async move {
+                connection
+                    .execute("SET SESSION TRANSACTION READ ONLY")
+                    .await?;
+                Ok(())
+            })
+        });
+    }
+    return pool_options.connect_with(options).await;
+}
+
+/// Runs one statement from the SQL editor on `executor`. It counts as returning rows if the
+/// driver sent any, or if it is a query that could have.
+async fn run_input_query<'e, E>(executor: E, input: &str) -> Result<QueryResult, sqlx::Error>
+where
+    E: Executor<'e, Database = MySql>,
+{
+    let mut stream = sqlx::query(input).fetch_many(executor);
+    let mut rows = vec![];
+    let mut rows_affected = 0;
+    let mut last_insert_id = 0;
+    while let Some(step) = stream.try_next().await? {
+        match step {
+            Either::Left(result) => {
+                rows_affected += result.rows_affected();
+                last_insert_id = result.last_insert_id();
+            }
+            Either::Right(row) => rows.push(row),
+        }
+    }
+    if !rows.is_empty() || is_row_returning_query(input, Dialect::MySql) {
+        let (headers, records) = parse_sql_table_rows(rows);
+        return Ok(QueryResult::Rows(headers, records));
+    }
+
+    return Ok(QueryResult::Summary {
+        rows_affected: rows_affected,
+        last_insert_id: Some(last_insert_id),
+    });
+}
+
+fn bind_value<'q>(
+    query: Query<'q, MySql, MySqlArguments>,
+    value: &CellValue,
+) -> Query<'q, MySql, MySqlArguments> {
+    match value.clone() {
+        CellValue::Null => query.bind(None::<String>),
+        CellValue::Bool(value) => query.bind(value),
+        CellValue::Int(value) => query.bind(value),
+        CellValue::UInt(value) => query.bind(value),
+        CellValue::Float(value) => query.bind(value),
+        CellValue::Decimal(value) | CellValue::Text(value) => query.bind(value),
+        CellValue::Bytes(value) => query.bind(value),
+        CellValue::Date(value) => query.bind(value),
+        CellValue::Time(value) => query.bind(value),
+        CellValue::DateTime(value) => query.bind(value),
+        CellValue::Timestamp(value) => query.bind(value),
+        CellValue::Json(value) => query.bind(value.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::option_file_socket;
+
+    fn socket_for(url: &str, host: &str) -> Option<String> {
+        let url = Url::parse(url).unwrap();
+        return option_file_socket(&url, host, Some("/tmp/mysql.sock".to_string()));
+    }
+
+    #[test]
+    fn option_file_socket_only_for_localhost() {
+        assert_eq!(
+            socket_for("mysql://root@localhost/shop", "localhost").as_deref(),
+            Some("/tmp/mysql.sock")
+        );
+        assert_eq!(
+            socket_for("mysql:///shop", "localhost").as_deref(),
+            Some("/tmp/mysql.sock")
+        );
+        assert_eq!(
+            socket_for("mysql://root@db.example.com/shop", "db.example.com"),
+            None
+        );
+        assert_eq!(socket_for("mysql:///shop", "db.example.com"), None);
+        assert_eq!(
+            socket_for(
+                "mysql://root@localhost/shop?socket=/run/my.sock",
+                "localhost"
+            ),
+            None
+        );
+    }
+}
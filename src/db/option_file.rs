@@ -0,0 +1,280 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use openssl::symm::{decrypt, Cipher};
+
+/// How deep `!include` may nest before a file is taken to include itself.
+const MAX_INCLUDE_DEPTH: usize = 10;
+
+/// The `[client]` options of the MySQL option files that say where and as whom to connect,
+/// for filling in what a URL leaves out.
+#[derive(Default)]
+pub struct ClientOptions {
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub user: Option<String>,
+    pub password: Option<String>,
+    pub socket: Option<String>,
+    pub database: Option<String>,
+}
+
+impl ClientOptions {
+    /// Reads the option files the `mysql` client reads, each overriding the ones before:
+    /// `/etc/my.cnf`, `/etc/mysql/my.cnf`, `$MYSQL_HOME/my.cnf`, `~/.my.cnf` and the
+    /// obfuscated `~/.mylogin.cnf` written by `mysql_config_editor`. With
+    /// `--defaults-file=<path>` on the command line, that file replaces all but the last one.
+    /// Missing files are skipped, except for the one passed on the command line.
+    pub fn load() -> anyhow::Result<Self> {
+        let mut options = Self::default();
+        match defaults_file_from_args() {
+            Some(path) => {
+                let content = fs::read_to_string(&path)
+                    .with_context(|| format!("Could not read {}", path.display()))?;
+                options.read(&content, &path, 0);
+            }
+            None => {
+                let mut paths = vec![
+                    PathBuf::from("/etc/my.cnf"),
+                    PathBuf::from("/etc/mysql/my.cnf"),
+                ];
+                if let Ok(mysql_home) = env::var("MYSQL_HOME") {
+                    paths.push(Path::new(&mysql_home).join("my.cnf"));
+                }
+                if let Some(home) = dirs::home_dir() {
+                    paths.push(home.join(".my.cnf"));
+                }
+                for path in paths.iter() {
+                    options.read_file(path, 0);
+                }
+            }
+        }
+        if let Some(home) = dirs::home_dir() {
+            let path = home.join(".mylogin.cnf");
+            if let Some(content) = fs::read(&path)
+                .ok()
+                .and_then(|data| decrypt_login_file(&data))
+            {
+                options.read(&content, &path, 0);
+            }
+        }
+        return Ok(options);
+    }
+
+    fn read_file(&mut self, path: &Path, depth: usize) {
+        if let Ok(content) = fs::read_to_string(path) {
+            self.read(&content, path, depth);
+        }
+    }
+
+    /// Takes the options of the `[client]` section of `content`, read from `path`, and
+    /// follows its `!include` and `!includedir` lines.
+    fn read(&mut self, content: &str, path: &Path, depth: usize) {
+        let mut in_client = false;
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if let Some(section) = line.strip_prefix('[') {
+                in_client = section.trim_end_matches(']').trim() == "client";
+                continue;
+            }
+            if let Some(include) = line.strip_prefix("!includedir") {
+                if depth < MAX_INCLUDE_DEPTH {
+                    self.read_dir(&relative_to(path, include.trim()), depth + 1);
+                }
+                continue;
+            }
+            if let Some(include) = line.strip_prefix("!include") {
+                if depth < MAX_INCLUDE_DEPTH {
+                    self.read_file(&relative_to(path, include.trim()), depth + 1);
+                }
+                continue;
+            }
+            if !in_client {
+                continue;
+            }
+            let (key, value) = match line.split_once('=') {
+                Some((key, value)) => (key.trim(), parse_value(value)),
+                None => (line, String::new()),
+            };
+            // An empty value means the same as leaving the option out, e.g. a bare
+            // `password` asks the mysql client to prompt for it
+            if value.is_empty() {
+                continue;
+            }
+            match key.replace('-', "_").as_str() {
+                "host" => self.host = Some(value),
+                "port" => self.port = value.parse().ok().or(self.port),
+                "user" => self.user = Some(value),
+                "password" => self.password = Some(value),
+                "socket" => self.socket = Some(value),
+                "database" => self.database = Some(value),
+                _ => {}
+            }
+        }
+    }
+
+    /// Reads the `.cnf` files of `dir` in name order, like `!includedir` does.
+    fn read_dir(&mut self, dir: &Path, depth: usize) {
+        let mut paths: Vec<PathBuf> = match fs::read_dir(dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|extension| extension == "cnf"))
+                .collect(),
+            Err(_) => return,
+        };
+        paths.sort();
+        for path in paths.iter() {
+            self.read_file(path, depth);
+        }
+    }
+}
+
+/// The path given by `--defaults-file=<path>` on the command line, if any.
+pub fn defaults_file_from_args() -> Option<PathBuf> {
+    return env::args()
+        .skip(1)
+        .find_map(|arg| arg.strip_prefix("--defaults-file=").map(PathBuf::from));
+}
+
+fn relative_to(path: &Path, include: &str) -> PathBuf {
+    return match path.parent() {
+        Some(dir) => dir.join(include),
+        None => PathBuf::from(include),
+    };
+}
+
+/// The value after `=`: unquoted up to a `#` comment, or between matching quotes,
+/// with backslash escapes resolved either way.
+fn parse_value(raw: &str) -> String {
+    let raw = raw.trim();
+    let (text, quote) = match raw.chars().next() {
+        Some(quote @ ('"' | '\'')) => (&raw[1..], Some(quote)),
+        _ => (raw, None),
+    };
+    let mut value = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => value.push('\n'),
+                Some('t') => value.push('\t'),
+                Some('r') => value.push('\r'),
+                Some('b') => value.push('\u{8}'),
+                Some('s') => value.push(' '),
+                Some(c) => value.push(c),
+                None => value.push('\\'),
+            },
+            c if Some(c) == quote => break,
+            '#' if quote.is_none() => break,
+            c => value.push(c),
+        }
+    }
+    return match quote {
+        Some(_) => value,
+        None => value.trim_end().to_string(),
+    };
+}
+
+/// The text of a `.mylogin.cnf`: after 4 unused bytes comes a 20 byte key, folded into an
+/// AES-128 key, then each line as a 4 byte little-endian length and that many bytes of
+/// AES-128-ECB cipher text. None if the file is not laid out like that.
+fn decrypt_login_file(data: &[u8]) -> Option<String> {
+    let key_material = data.get(4..24)?;
+    let mut key = [0u8; 16];
+    for (i, byte) in key_material.iter().enumerate() {
+        key[i % 16] ^= byte;
+    }
+    let mut content = vec![];
+    let mut rest = &data[24..];
+    while rest.len() >= 4 {
+        let length = u32::from_le_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        let cipher_text = rest.get(4..4 + length)?;
+        content.extend(decrypt(Cipher::aes_128_ecb(), &key, None, cipher_text).ok()?);
+        rest = &rest[4 + length..];
+    }
+    return String::from_utf8(content).ok();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use openssl::symm::{encrypt, Cipher};
+
+    use super::{decrypt_login_file, parse_value, ClientOptions};
+
+    #[test]
+    fn parses_plain_and_quoted_values() {
+        assert_eq!(parse_value(" secret  # comment"), "secret");
+        assert_eq!(parse_value("\"pass # word\" # comment"), "pass # word");
+        assert_eq!(parse_value("'it\\'s'"), "it's");
+        assert_eq!(parse_value("a\\sb\\tc\\\\"), "a b\tc\\");
+        assert_eq!(parse_value("\"  padded  \""), "  padded  ");
+        assert_eq!(parse_value(""), "");
+    }
+
+    #[test]
+    fn reads_only_the_client_section() {
+        let mut options = ClientOptions::default();
+        let content = "[mysqld]\nport = 1\n\n[client]\nhost=db.local\nport = 3307\n\
+                       # user = nobody\nuser = 'app'\npassword\nskip-ssl\n";
+        options.read(content, &env::temp_dir().join("my.cnf"), 0);
+        assert_eq!(options.host.as_deref(), Some("db.local"));
+        assert_eq!(options.port, Some(3307));
+        assert_eq!(options.user.as_deref(), Some("app"));
+        assert_eq!(options.password, None);
+    }
+
+    #[test]
+    fn follows_includes_relative_to_the_file() {
+        let dir = env::temp_dir().join(format!("sql-cli-rust-options-{}", std::process::id()));
+        fs::create_dir_all(dir.join("conf.d")).unwrap();
+        fs::write(dir.join("extra.cnf"), "[client]\nuser = included\n").unwrap();
+        fs::write(dir.join("conf.d/b.cnf"), "[client]\nport = 3308\n").unwrap();
+        fs::write(
+            dir.join("conf.d/a.cnf"),
+            "[client]\nport = 3307\nhost = a\n",
+        )
+        .unwrap();
+        fs::write(dir.join("conf.d/ignored.txt"), "[client]\nhost = txt\n").unwrap();
+        // Includes itself, which stops at the depth limit
+        fs::write(dir.join("loop.cnf"), "!include loop.cnf\n").unwrap();
+
+        let mut options = ClientOptions::default();
+        let content = "[client]\nuser = first\n!include extra.cnf\n!includedir conf.d\n\
+                       !include loop.cnf\n!include missing.cnf\n";
+        options.read(content, &dir.join("my.cnf"), 0);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(options.user.as_deref(), Some("included"));
+        assert_eq!(options.port, Some(3308));
+        assert_eq!(options.host.as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn decrypts_login_files() {
+        let mut data = vec![0u8; 4];
+        let key_material: Vec<u8> = (1..=20).collect();
+        data.extend(&key_material);
+        let mut key = [0u8; 16];
+        for (i, byte) in key_material.iter().enumerate() {
+            key[i % 16] ^= byte;
+        }
+        for line in ["[client]\n", "user = login\n"] {
+            let cipher_text = encrypt(Cipher::aes_128_ecb(), &key, None, line.as_bytes()).unwrap();
+            data.extend((cipher_text.len() as u32).to_le_bytes());
+            data.extend(cipher_text);
+        }
+        assert_eq!(
+            decrypt_login_file(&data).as_deref(),
+            Some("[client]\nuser = login\n")
+        );
+        assert_eq!(decrypt_login_file(&data[..10]), None);
+        assert_eq!(decrypt_login_file(&data[..data.len() - 1]), None);
+    }
+}
@@ -1,22 +1,54 @@
 use sqlx::mysql::MySqlRow;
+use sqlx::postgres::PgRow;
+use sqlx::sqlite::SqliteRow;
 use sqlx::{Column, Row};
 
-use crate::utils;
+use crate::{model::cell::CellValue, utils};
 
-pub fn parse_sql_db(rows: Vec<MySqlRow>) -> Vec<String> {
-    let tables: Vec<String> = rows.iter().map(|row| row.get(0)).collect();
+use super::lexer::{tokenize, Dialect, TokenKind};
+
+pub trait SqlRow: Row {
+    fn cell_value(&self, index: usize) -> CellValue;
+}
+
+impl SqlRow for MySqlRow {
+    fn cell_value(&self, index: usize) -> CellValue {
+        return utils::decode_mysql_cell_value(self, index);
+    }
+}
+
+impl SqlRow for PgRow {
+    fn cell_value(&self, index: usize) -> CellValue {
+        return utils::decode_pg_cell_value(self, index);
+    }
+}
+
+impl SqlRow for SqliteRow {
+    fn cell_value(&self, index: usize) -> CellValue {
+        return utils::decode_sqlite_cell_value(self, index);
+    }
+}
+
+pub fn parse_sql_db<R: SqlRow>(rows: Vec<R>) -> Vec<String> {
+    let tables: Vec<String> = rows
+        .iter()
+        .map(|row| row.cell_value(0).to_string())
+        .collect();
     return tables;
 }
 
-pub fn parse_sql_tables(rows: Vec<MySqlRow>) -> Vec<String> {
+pub fn parse_sql_tables<R: SqlRow>(rows: Vec<R>) -> Vec<String> {
     let tables: Vec<String> = rows
         .iter()
-        .map(|row| row.get::<String, _>("Name"))
+        .filter_map(|row| {
+            let index = row.columns().iter().position(|c| c.name() == "Name")?;
+            Some(row.cell_value(index).to_string())
+        })
         .collect();
     return tables;
 }
 
-pub fn parse_sql_table_rows(table_rows: Vec<MySqlRow>) -> (Vec<String>, Vec<Vec<String>>) {
+pub fn parse_sql_table_rows<R: SqlRow>(table_rows: Vec<R>) -> (Vec<String>, Vec<Vec<CellValue>>) {
     let mut headers = vec![];
     let mut records = vec![];
 
@@ -28,15 +60,84 @@ pub fn parse_sql_table_rows(table_rows: Vec<MySqlRow>) -> (Vec<String>, Vec<Vec<
             .collect();
 
         let mut record = vec![];
-        for column in table_row.columns() {
-            let column_name = column.name();
-            record.push(utils::convert_column_value_to_string(
-                &table_row,
-                column_name,
-            ));
+        for index in 0..table_row.columns().len() {
+            record.push(table_row.cell_value(index));
         }
         records.push(record);
     }
 
     return (headers, records);
 }
+
+/// Whether `query` returns rows even when there are none to show, judged from its first
+/// keyword after any comments and from a `RETURNING` clause.
+pub fn is_row_returning_query(query: &str, dialect: Dialect) -> bool {
+    let words: Vec<String> = tokenize(query, dialect)
+        .iter()
+        .filter(|token| matches!(token.kind, TokenKind::Keyword | TokenKind::Identifier))
+        .map(|token| token.text(query).to_uppercase())
+        .collect();
+    let keyword = match words.first() {
+        Some(keyword) => keyword.as_str(),
+        None => return false,
+    };
+
+    return matches!(
+        keyword,
+        "SELECT"
+            | "SHOW"
+            | "DESCRIBE"
+            | "DESC"
+            | "EXPLAIN"
+            | "WITH"
+            | "VALUES"
+            | "TABLE"
+            | "PRAGMA"
+    ) || words.iter().any(|word| word == "RETURNING");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_row_returning_query;
+    use crate::db::lexer::Dialect;
+
+    #[test]
+    fn classifies_by_first_keyword() {
+        assert!(is_row_returning_query("SELECT 1", Dialect::MySql));
+        assert!(is_row_returning_query(
+            "  (select 1) union (select 2)",
+            Dialect::MySql
+        ));
+        assert!(is_row_returning_query("show tables", Dialect::MySql));
+        assert!(!is_row_returning_query(
+            "UPDATE t SET a = 1",
+            Dialect::MySql
+        ));
+        assert!(!is_row_returning_query("", Dialect::MySql));
+    }
+
+    #[test]
+    fn skips_leading_comments() {
+        assert!(is_row_returning_query("-- note\nSELECT 1", Dialect::MySql));
+        assert!(is_row_returning_query(
+            "/* a */ # b\nSELECT 1",
+            Dialect::MySql
+        ));
+        assert!(!is_row_returning_query(
+            "-- SELECT\nDELETE FROM t",
+            Dialect::MySql
+        ));
+    }
+
+    #[test]
+    fn pragma_and_returning_return_rows() {
+        assert!(is_row_returning_query(
+            "PRAGMA table_info(users)",
+            Dialect::MySql
+        ));
+        assert!(is_row_returning_query(
+            "INSERT INTO t (a) VALUES (1) RETURNING id",
+            Dialect::MySql
+        ));
+    }
+}
@@ -0,0 +1,388 @@
+use std::{str::FromStr, sync::RwLock, time::Instant};
+
+use async_trait::async_trait;
+use futures_util::TryStreamExt;
+use sqlx::{
+    postgres::{PgArguments, PgConnectOptions, PgPoolOptions},
+    query::Query,
+    Connection, Either, Executor, Pool, Postgres, Transaction,
+};
+
+use tokio::sync::Mutex;
+use url::Url;
+
+use crate::{
+    model::{cell::CellValue, table::RecordSort},
+    utils::quote_identifier,
+};
+
+use super::{
+    lexer::Dialect,
+    parser::{is_row_returning_query, parse_sql_db, parse_sql_table_rows, parse_sql_tables},
+    sql_client::{
+        no_transaction_error, order_by_clause, script_error, where_clause, QueryResult,
+        RunningStatement, ScriptProgress, SqlClient, StatementResult,
+    },
+    statement::{literal, Statement},
+};
+
+const GET_TABLES_QUERY: &str = r#"
+SELECT (CASE WHEN table_schema = 'public' THEN table_name
+             ELSE table_schema || '.' || table_name END)::text AS "Name"
+FROM information_schema.tables
+WHERE table_catalog = $1
+  AND table_schema NOT IN ('pg_catalog', 'information_schema')
+ORDER BY table_schema, table_name
+"#;
+
+// Shaped like MySQL's `SHOW COLUMNS` so the Columns tab looks the same on both backends.
+const GET_COLUMNS_QUERY: &str = r#"
+SELECT a.attname::text AS "Field",
+       format_type(a.atttypid, a.atttypmod) AS "Type",
+       CASE WHEN a.attnotnull THEN 'NO' ELSE 'YES' END AS "Null",
+       CASE WHEN EXISTS (
+           SELECT 1 FROM pg_index i
+           WHERE i.indrelid = a.attrelid AND i.indisprimary AND a.attnum = ANY(i.indkey)
+       ) THEN 'PRI' WHEN EXISTS (
+           SELECT 1 FROM pg_index i
+           WHERE i.indrelid = a.attrelid AND i.indisunique AND i.indpred IS NULL
+             AND i.indnatts = 1 AND i.indkey[0] = a.attnum
+       ) THEN 'UNI' ELSE '' END AS "Key",
+       pg_get_expr(d.adbin, d.adrelid) AS "Default",
+       CASE WHEN a.attidentity <> '' THEN 'identity' ELSE '' END AS "Extra"
+FROM pg_attribute a
+LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+WHERE a.attrelid = $1::regclass AND a.attnum > 0 AND NOT a.attisdropped
+ORDER BY a.attnum
+"#;
+
+pub struct PostgresClient {
+    options: PgConnectOptions,
+    /// The database named by the URL, to open first.
+    default_database: Option<String>,
+    read_only: bool,
+    // Swapped for a pool on the new database by `reconnect`.
+    pool: RwLock<Pool<Postgres>>,
+    progress: ScriptProgress,
+    // Connections are not `Sync`, so the open transaction sits behind a mutex.
+    transaction: Mutex<Option<Transaction<'static, Postgres>>>,
+}
+
+impl PostgresClient {
+    pub async fn new(base_db_url: String, read_only: bool) -> anyhow::Result<Self> {
+        let options = PgConnectOptions::from_str(&base_db_url)?;
+        let default_database = Url::parse(&base_db_url)?
+            .path()
+            .trim_start_matches('/')
+            .to_string();
+
+        Ok(Self {
+            pool: RwLock::new(connect_pool(options.clone(), read_only).await?),
+            options: options,
+            default_database: Some(default_database).filter(|database| !database.is_empty()),
+            read_only: read_only,
+            progress: ScriptProgress::new(),
+            transaction: Mutex::new(None),
+        })
+    }
+
+    fn pool(&self) -> Pool<Postgres> {
+        return self.pool.read().unwrap().clone();
+    }
+}
+
+#[async_trait]
+impl SqlClient for PostgresClient {
+    async fn reconnect(&self, new_database: String) -> anyhow::Result<()> {
+        let options = self.options.clone().database(&new_database);
+        let pool = connect_pool(options, self.read_only).await?;
+        *self.pool.write().unwrap() = pool;
+        Ok(())
+    }
+
+    fn default_database(&self) -> Option<String> {
+        return self.default_database.clone();
+    }
+
+    async fn ping(&self) -> anyhow::Result<()> {
+        self.pool().acquire().await?.ping().await?;
+        Ok(())
+    }
+
+    async fn get_database_list(&self) -> anyhow::Result<Vec<String>> {
+        let get_db_query =
+            "SELECT datname::text FROM pg_database WHERE NOT datistemplate ORDER BY datname";
+        let db_rows = sqlx::query(get_db_query).fetch_all(&self.pool()).await?;
+
+        return Ok(parse_sql_db(db_rows));
+    }
+
+    async fn get_table_list(&self, db_name: String) -> anyhow::Result<Vec<String>> {
+        let table_rows = sqlx::query(GET_TABLES_QUERY)
+            .bind(db_name)
+            .fetch_all(&self.pool())
+            .await?;
+
+        return Ok(parse_sql_tables(table_rows));
+    }
+
+    async fn get_table_records(
+        &self,
+        table_name: String,
+        filter: Option<&str>,
+        sort: Option<&RecordSort>,
+        key_columns: &[String],
+        limit: usize,
+        offset: usize,
+    ) -> Result<(Vec<String>, Vec<Vec<CellValue>>), sqlx::Error> {
+        let get_records_query = format!(
+            "{} {} {} {} LIMIT {} OFFSET {}",
+            "SELECT * FROM",
+            self.quote_table_name(&table_name),
+            where_clause(filter),
+            order_by_clause(sort, key_columns, '"'),
+            limit,
+            offset
+        );
+        let record_rows = sqlx::query(get_records_query.as_str())
+            .fetch_all(&self.pool())
+            .await?;
+
+        return Ok(parse_sql_table_rows(record_rows));
+    }
+
+    async fn get_table_record_count(
+        &self,
+        table_name: String,
+        filter: Option<&str>,
+    ) -> Result<u64, sqlx::Error> {
+        let get_count_query = format!(
+            "{} {} {}",
+            "SELECT COUNT(*) FROM",
+            self.quote_table_name(&table_name),
+            where_clause(filter)
+        );
+        let count: i64 = sqlx::query_scalar(get_count_query.as_str())
+            .fetch_one(&self.pool())
+            .await?;
+
+        return Ok(count as u64);
+    }
+
+    async fn get_table_columns(
+        &self,
+        table_name: String,
+    ) -> anyhow::Result<(Vec<String>, Vec<Vec<CellValue>>)> {
+        let column_rows = sqlx::query(GET_COLUMNS_QUERY)
+            .bind(self.quote_table_name(&table_name))
+            .fetch_all(&self.pool())
+            .await?;
+
+        return Ok(parse_sql_table_rows(column_rows));
+    }
+
+    async fn execute_script(
+        &self,
+        statements: &[String],
+        continue_on_error: bool,
+    ) -> Vec<StatementResult> {
+        let _script = self.progress.begin();
+        let mut connection = match self.pool().acquire().await {
+            Ok(connection) => connection,
+            Err(e) => return script_error(statements, e),
+        };
+        let connection_id = sqlx::query_scalar::<_, i32>("SELECT pg_backend_pid()")
+            .fetch_one(&mut *connection)
+            .await
+            .ok()
+            .map(i64::from);
+        let mut results = vec![];
+        for (index, sql) in statements.iter().enumerate() {
+            if !self.progress.start(connection_id, index) {
+                break;
+            }
+            let started = Instant::now();
+            let result = run_input_query(&mut *connection, sql).await;
+            let failed = result.is_err();
+            results.push(StatementResult {
+                sql: sql.clone(),
+                result: result,
+                duration: started.elapsed(),
+            });
+            if failed && !continue_on_error {
+                break;
+            }
+        }
+        return results;
+    }
+
+    fn running_statement(&self) -> Option<RunningStatement> {
+        return self.progress.running();
+    }
+
+    async fn cancel_query(&self) -> Result<bool, sqlx::Error> {
+        let running = match self.progress.cancel() {
+            Some(running) => running,
+            None => return Ok(true),
+        };
+        if let Some(connection_id) = running.connection_id {
+            // Goes to another connection of the pool, the script's own is checked out
+            sqlx::query("SELECT pg_cancel_backend($1)")
+                .bind(connection_id as i32)
+                .execute(&self.pool())
+                .await?;
+        }
+        Ok(true)
+    }
+
+    async fn execute_transaction(&self, statements: &[Statement]) -> Result<u64, sqlx::Error> {
+        let mut transaction = self.pool().begin().await?;
+        let mut rows_affected = 0;
+        for statement in statements.iter() {
+            let mut query = sqlx::query(statement.sql.as_str());
+            for param in statement.params.iter() {
+                query = bind_value(query, param);
+            }
+            rows_affected += query.execute(&mut transaction).await?.rows_affected();
+        }
+        transaction.commit().await?;
+        return Ok(rows_affected);
+    }
+
+    async fn begin_transaction(&self) -> Result<(), sqlx::Error> {
+        *self.transaction.lock().await = Some(self.pool().begin().await?);
+        Ok(())
+    }
+
+    async fn execute_in_transaction(&self, statement: &Statement) -> Result<u64, sqlx::Error> {
+        let mut transaction = self.transaction.lock().await;
+        let transaction = transaction.as_mut().ok_or_else(no_transaction_error)?;
+        let mut query = sqlx::query(statement.sql.as_str());
+        for param in statement.params.iter() {
+            query = bind_value(query, param);
+        }
+        let result = query.execute(transaction).await?;
+        return Ok(result.rows_affected());
+    }
+
+    async fn commit_transaction(&self) -> Result<(), sqlx::Error> {
+        let transaction = self
+            .transaction
+            .lock()
+            .await
+            .take()
+            .ok_or_else(no_transaction_error)?;
+        return transaction.commit().await;
+    }
+
+    async fn rollback_transaction(&self) -> Result<(), sqlx::Error> {
+        let transaction = self
+            .transaction
+            .lock()
+            .await
+            .take()
+            .ok_or_else(no_transaction_error)?;
+        return transaction.rollback().await;
+    }
+
+    fn dialect(&self) -> Dialect {
+        return Dialect::Postgres;
+    }
+
+    fn quote_identifier(&self, identifier: &str) -> String {
+        return quote_identifier(identifier, '"');
+    }
+
+    fn quote_table_name(&self, table_name: &str) -> String {
+        return table_name
+            .split('.')
+            .map(|part| quote_identifier(part, '"'))
+            .collect::<Vec<String>>()
+            .join(".");
+    }
+
+    fn quote_literal(&self, value: &CellValue) -> String {
+        return match value {
+            // `X'...'` is a bit string to Postgres
+            CellValue::Bytes(_) => {
+                format!("'\\x{}'::bytea", value.to_string().trim_start_matches("0x"))
+            }
+            value => literal(value, false),
+        };
+    }
+
+    /// Values typed by the user are bound as text, so cast them to the column's type.
+    fn placeholder(&self, index: usize, column_type: &str) -> String {
+        if column_type.is_empty() {
+            return format!("${}", index);
+        }
+        return format!("CAST(${} AS {})", index, column_type);
+    }
+}
+
+/// Opens a pool with `options` whose sessions refuse to write if `read_only` is set.
+async fn connect_pool(
+    options: PgConnectOptions,
+    read_only: bool,
+) -> Result<Pool<Postgres>, sqlx::Error> {
+    let mut pool_options = PgPoolOptions::new();
+    if read_only {
+        pool_options = pool_options.after_connect(|connection| {
+            Box::pin(async move {
+                connection
+                    .execute("SET SESSION CHARACTERISTICS AS TRANSACTION READ ONLY")
+                    .await?;
+                Ok(())
+            })
+        });
+    }
+    return pool_options.connect_with(options).await;
+}
+
+/// Runs one statement from the SQL editor on `executor`. It counts as returning rows if the
+/// driver sent any, or if it is a query that could have.
+async fn run_input_query<'e, E>(executor: E, input: &str) -> Result<QueryResult, sqlx::Error>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    let mut stream = sqlx::query(input).fetch_many(executor);
+    let mut rows = vec![];
+    let mut rows_affected = 0;
+    while let Some(step) = stream.try_next().await? {
+        match step {
+            Either::Left(result) => {
+                rows_affected += result.rows_affected();
+            }
+            Either::Right(row) => rows.push(row),
+        }
+    }
+    if !rows.is_empty() || is_row_returning_query(input, Dialect::Postgres) {
+        let (headers, records) = parse_sql_table_rows(rows);
+        return Ok(QueryResult::Rows(headers, records));
+    }
+
+    return Ok(QueryResult::Summary {
+        rows_affected: rows_affected,
+        last_insert_id: None,
+    });
+}
+
+fn bind_value<'q>(
+    query: Query<'q, Postgres, PgArguments>,
+    value: &CellValue,
+) -> Query<'q, Postgres, PgArguments> {
+    match value.clone() {
+        CellValue::Null => query.bind(None::<String>),
+        CellValue::Bool(value) => query.bind(value),
+        CellValue::Int(value) => query.bind(value),
+        CellValue::UInt(value) => query.bind(value as i64),
+        CellValue::Float(value) => query.bind(value),
+        CellValue::Decimal(value) | CellValue::Text(value) => query.bind(value),
+        CellValue::Bytes(value) => query.bind(value),
+        CellValue::Date(value) => query.bind(value),
+        CellValue::Time(value) => query.bind(value),
+        CellValue::DateTime(value) => query.bind(value),
+        CellValue::Timestamp(value) => query.bind(value),
+        CellValue::Json(value) => query.bind(value.to_string()),
+    }
+}
@@ -1,77 +1,285 @@
-use std::env;
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
 
-use sqlx::{mysql::MySqlQueryResult, MySql, MySqlPool, Pool};
+use async_trait::async_trait;
+use sqlx::sqlite::SqliteConnectOptions;
 
-use super::parser::{parse_sql_db, parse_sql_table_rows, parse_sql_tables};
+use crate::{
+    model::{
+        cell::CellValue,
+        table::{RecordSort, SortOrder},
+    },
+    utils::quote_identifier,
+};
 
-pub struct MySqlClient {
-    pub base_db_url: String,
-    pub pool: Pool<MySql>,
+use super::{
+    lexer::Dialect, mysql_client::MySqlClient, postgres_client::PostgresClient,
+    sqlite_client::SqliteClient, statement::Statement,
+};
+
+pub enum QueryResult {
+    Rows(Vec<String>, Vec<Vec<CellValue>>),
+    Summary {
+        rows_affected: u64,
+        last_insert_id: Option<u64>,
+    },
+}
+
+/// The outcome of one statement of a script and how long it took.
+pub struct StatementResult {
+    pub sql: String,
+    pub result: Result<QueryResult, sqlx::Error>,
+    pub duration: Duration,
+}
+
+/// The statement a script is on, for showing progress and cancelling it.
+#[derive(Clone, Copy)]
+pub struct RunningStatement {
+    /// The server's id for the connection running the script, where the backend has one.
+    pub connection_id: Option<i64>,
+    pub index: usize,
+    pub started: Instant,
 }
 
-impl MySqlClient {
-    pub async fn new() -> Self {
-        let base_db_url = env::var("DATABASE_URL").unwrap().to_string();
+/// Shared between a backend's running `execute_script` and the `cancel_query` calls
+/// made while it waits, which come in through `&self` as well.
+pub struct ScriptProgress {
+    running: Mutex<Option<RunningStatement>>,
+    cancelled: AtomicBool,
+}
 
+impl ScriptProgress {
+    pub fn new() -> Self {
         Self {
-            base_db_url: base_db_url.clone(),
-            pool: MySqlPool::connect(&base_db_url).await.unwrap(),
+            running: Mutex::new(None),
+            cancelled: AtomicBool::new(false),
         }
     }
 
-    pub async fn reconnect(&mut self, new_database: String) {
-        let new_db_url = format!("{}/{}", self.base_db_url, new_database);
-        self.pool = MySqlPool::connect(&new_db_url).await.unwrap();
+    pub fn running(&self) -> Option<RunningStatement> {
+        return *self.running.lock().unwrap();
     }
 
-    pub async fn get_database_list(&self) -> Vec<String> {
-        let get_db_query = format!("{}", "SHOW DATABASES");
-        let db_rows = sqlx::query(&get_db_query.as_str())
-            .fetch_all(&self.pool)
-            .await
-            .unwrap();
+    /// Clears a cancel left from an earlier script, on entering `execute_script`. The
+    /// script counts as running until the returned guard drops, which also happens
+    /// when the script's future is dropped part way.
+    pub fn begin(&self) -> ScriptGuard<'_> {
+        self.cancelled.store(false, Ordering::SeqCst);
+        return ScriptGuard { progress: self };
+    }
 
-        return parse_sql_db(db_rows);
+    /// Moves the script on to statement `index`. Returns false if it was cancelled and
+    /// should stop instead.
+    pub fn start(&self, connection_id: Option<i64>, index: usize) -> bool {
+        if self.cancelled.load(Ordering::SeqCst) {
+            return false;
+        }
+        *self.running.lock().unwrap() = Some(RunningStatement {
+            connection_id: connection_id,
+            index: index,
+            started: Instant::now(),
+        });
+        return true;
     }
 
-    pub async fn get_table_list(&self, db_name: String) -> Vec<String> {
-        let get_tables_query = format!("{} {}", "SHOW TABLE STATUS FROM", db_name);
-        let table_rows = sqlx::query(&get_tables_query.as_str())
-            .fetch_all(&self.pool)
-            .await
-            .unwrap();
+    /// Stops the script after its current statement and returns that statement.
+    pub fn cancel(&self) -> Option<RunningStatement> {
+        self.cancelled.store(true, Ordering::SeqCst);
+        return self.running();
+    }
 
-        return parse_sql_tables(table_rows);
+    fn finish(&self) {
+        *self.running.lock().unwrap() = None;
     }
+}
 
-    pub async fn get_table_records(&self, table_name: String) -> (Vec<String>, Vec<Vec<String>>) {
-        let get_records_query = format!("{} {}", "SELECT * FROM", table_name);
-        let record_rows = sqlx::query(&get_records_query.as_str())
-            .fetch_all(&self.pool)
-            .await
-            .unwrap();
+/// Returned by `ScriptProgress::begin`, finishes the script when dropped.
+pub struct ScriptGuard<'a> {
+    progress: &'a ScriptProgress,
+}
 
-        return parse_sql_table_rows(record_rows);
+impl Drop for ScriptGuard<'_> {
+    fn drop(&mut self) {
+        self.progress.finish();
     }
+}
 
-    pub async fn get_table_columns(&self, table_name: String) -> (Vec<String>, Vec<Vec<String>>) {
-        let get_records_query = format!("{} {}", "SHOW COLUMNS FROM", table_name);
-        let column_rows = sqlx::query(&get_records_query.as_str())
-            .fetch_all(&self.pool)
-            .await
-            .unwrap();
+#[async_trait]
+pub trait SqlClient: Send + Sync {
+    async fn reconnect(&self, new_database: String) -> anyhow::Result<()>;
+    /// The database to open first when none is asked for.
+    fn default_database(&self) -> Option<String>;
+    /// Checks that the server still answers, on a connection the pool reopens if it has to.
+    async fn ping(&self) -> anyhow::Result<()>;
+    async fn get_database_list(&self) -> anyhow::Result<Vec<String>>;
+    async fn get_table_list(&self, db_name: String) -> anyhow::Result<Vec<String>>;
+    async fn get_table_records(
+        &self,
+        table_name: String,
+        filter: Option<&str>,
+        sort: Option<&RecordSort>,
+        key_columns: &[String],
+        limit: usize,
+        offset: usize,
+    ) -> Result<(Vec<String>, Vec<Vec<CellValue>>), sqlx::Error>;
+    async fn get_table_record_count(
+        &self,
+        table_name: String,
+        filter: Option<&str>,
+    ) -> Result<u64, sqlx::Error>;
+    async fn get_table_columns(
+        &self,
+        table_name: String,
+    ) -> anyhow::Result<(Vec<String>, Vec<Vec<CellValue>>)>;
+    /// Runs `statements` in order on one connection, so session state such as variables and
+    /// an open transaction carries over from one to the next. Stops at the first error
+    /// unless `continue_on_error` is set.
+    async fn execute_script(
+        &self,
+        statements: &[String],
+        continue_on_error: bool,
+    ) -> Vec<StatementResult>;
+    /// The statement `execute_script` is running, if any.
+    fn running_statement(&self) -> Option<RunningStatement>;
+    /// Interrupts the statement `execute_script` is running from a separate connection
+    /// and stops the script there. Returns false if the backend cannot interrupt a
+    /// statement, in which case the script still stops once the statement is done.
+    async fn cancel_query(&self) -> Result<bool, sqlx::Error>;
+    /// Runs `statements` in order inside one transaction, which is rolled back if any fails.
+    async fn execute_transaction(&self, statements: &[Statement]) -> Result<u64, sqlx::Error>;
+    /// Opens a transaction that stays open across `execute_in_transaction` calls,
+    /// e.g. so an import can be committed or rolled back after reviewing its errors.
+    async fn begin_transaction(&self) -> Result<(), sqlx::Error>;
+    async fn execute_in_transaction(&self, statement: &Statement) -> Result<u64, sqlx::Error>;
+    async fn commit_transaction(&self) -> Result<(), sqlx::Error>;
+    async fn rollback_transaction(&self) -> Result<(), sqlx::Error>;
+    /// How this backend reads SQL, for splitting scripts and highlighting.
+    fn dialect(&self) -> Dialect;
+    fn quote_identifier(&self, identifier: &str) -> String;
+    fn quote_table_name(&self, table_name: &str) -> String;
+    /// `value` as a literal this backend reads back as the same value, for SQL written to files.
+    fn quote_literal(&self, value: &CellValue) -> String;
+    /// Placeholder for the `index`th (1-based) bound value of a column of `column_type`.
+    fn placeholder(&self, index: usize, column_type: &str) -> String;
+}
 
-        return parse_sql_table_rows(column_rows);
+/// Connects to `base_db_url`, picking the backend from its scheme. A bare path without
+/// a scheme is opened as a SQLite file. With `read_only`, every session refuses to write.
+pub async fn connect(base_db_url: String, read_only: bool) -> anyhow::Result<Arc<dyn SqlClient>> {
+    if base_db_url.starts_with("postgres://") || base_db_url.starts_with("postgresql://") {
+        return Ok(Arc::new(PostgresClient::new(base_db_url, read_only).await?));
+    }
+    if base_db_url.starts_with("sqlite:") {
+        let options = SqliteConnectOptions::from_str(&base_db_url)?.read_only(read_only);
+        return Ok(Arc::new(SqliteClient::new(options).await?));
+    }
+    if !base_db_url.contains("://") {
+        let options = SqliteConnectOptions::new()
+            .filename(&base_db_url)
+            .read_only(read_only);
+        return Ok(Arc::new(SqliteClient::new(options).await?));
     }
+    return Ok(Arc::new(MySqlClient::new(base_db_url, read_only).await?));
+}
 
-    pub async fn execute_input_query(
-        &self,
-        input: String,
-    ) -> Result<MySqlQueryResult, sqlx::Error> {
-        let query = format!("{}", input);
-        match sqlx::query(&query.as_str()).execute(&self.pool).await {
-            Ok(result) => return Ok(result),
-            Err(e) => return Err(e),
+pub fn no_transaction_error() -> sqlx::Error {
+    return sqlx::Error::Protocol(String::from("no transaction is open"));
+}
+
+/// The result of a script that could not get a connection: the error, reported on its first statement.
+pub fn script_error(statements: &[String], error: sqlx::Error) -> Vec<StatementResult> {
+    return match statements.first() {
+        Some(sql) => vec![StatementResult {
+            sql: sql.clone(),
+            result: Err(error),
+            duration: Duration::ZERO,
+        }],
+        None => vec![],
+    };
+}
+
+pub fn where_clause(filter: Option<&str>) -> String {
+    match filter {
+        Some(filter) if !filter.trim().is_empty() => format!("WHERE ({})", filter),
+        _ => String::new(),
+    }
+}
+
+/// Orders by `sort` and then by `key_columns`, so that pages read with LIMIT and OFFSET
+/// neither repeat nor skip rows. Without key columns, rows tied on the sort may do both.
+pub fn order_by_clause(sort: Option<&RecordSort>, key_columns: &[String], quote: char) -> String {
+    let mut terms: Vec<String> = sort
+        .map(|sort| {
+            format!(
+                "{} {}",
+                quote_identifier(&sort.column, quote),
+                match sort.order {
+                    SortOrder::Asc => "ASC",
+                    SortOrder::Desc => "DESC",
+                }
+            )
+        })
+        .into_iter()
+        .collect();
+    terms.extend(
+        key_columns
+            .iter()
+            .filter(|column| sort.is_none_or(|sort| sort.column != **column))
+            .map(|column| quote_identifier(column, quote)),
+    );
+    if terms.is_empty() {
+        return String::new();
+    }
+    return format!("ORDER BY {}", terms.join(", "));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{order_by_clause, ScriptProgress};
+    use crate::model::table::{RecordSort, SortOrder};
+
+    #[test]
+    fn orders_by_key_columns_without_a_sort() {
+        let key_columns = vec![String::from("id"), String::from("part")];
+        assert_eq!(
+            order_by_clause(None, &key_columns, '`'),
+            "ORDER BY `id`, `part`"
+        );
+        assert_eq!(order_by_clause(None, &[], '`'), "");
+    }
+
+    #[test]
+    fn breaks_sort_ties_by_key_columns() {
+        let sort = RecordSort {
+            column: String::from("name"),
+            order: SortOrder::Desc,
         };
+        let key_columns = vec![String::from("id"), String::from("name")];
+        assert_eq!(
+            order_by_clause(Some(&sort), &key_columns, '"'),
+            r#"ORDER BY "name" DESC, "id""#
+        );
+    }
+
+    #[test]
+    fn keeps_a_cancel_made_before_the_first_statement() {
+        let progress = ScriptProgress::new();
+        let script = progress.begin();
+        progress.cancel();
+        assert!(!progress.start(None, 0));
+        drop(script);
+
+        let script = progress.begin();
+        assert!(progress.start(None, 0));
+        assert!(progress.running().is_some());
+        // As when a script's future is dropped before it finishes
+        drop(script);
+        assert!(progress.running().is_none());
     }
 }
@@ -0,0 +1,337 @@
+use std::{sync::RwLock, time::Instant};
+
+use async_trait::async_trait;
+use futures_util::TryStreamExt;
+use sqlx::{
+    query::Query,
+    sqlite::{SqliteArguments, SqliteConnectOptions, SqlitePoolOptions},
+    Connection, Either, Executor, Pool, Sqlite, Transaction,
+};
+
+use tokio::sync::Mutex;
+
+use crate::{
+    model::{cell::CellValue, table::RecordSort},
+    utils::quote_identifier,
+};
+
+use super::{
+    lexer::Dialect,
+    parser::{is_row_returning_query, parse_sql_db, parse_sql_table_rows, parse_sql_tables},
+    sql_client::{
+        no_transaction_error, order_by_clause, script_error, where_clause, QueryResult,
+        RunningStatement, ScriptProgress, SqlClient, StatementResult,
+    },
+    statement::{literal, non_finite_literal, Statement},
+};
+
+// Shaped like MySQL's `SHOW COLUMNS` so the Columns tab looks the same on every backend.
+const GET_COLUMNS_QUERY: &str = r#"
+SELECT name AS "Field",
+       type AS "Type",
+       CASE WHEN "notnull" = 1 THEN 'NO' ELSE 'YES' END AS "Null",
+       CASE WHEN pk > 0 THEN 'PRI' WHEN EXISTS (
+           SELECT 1 FROM pragma_index_list(?1, ?2) AS i
+           WHERE i."unique" AND NOT i.partial
+             AND (SELECT group_concat(name) FROM pragma_index_info(i.name, ?2)) = c.name
+       ) THEN 'UNI' ELSE '' END AS "Key",
+       dflt_value AS "Default",
+       '' AS "Extra"
+FROM pragma_table_info(?1, ?2) AS c
+ORDER BY cid
+"#;
+
+pub struct SqliteClient {
+    // Set by `reconnect`, which only switches between attached schemas.
+    current_schema: RwLock<String>,
+    pool: Pool<Sqlite>,
+    progress: ScriptProgress,
+    // Connections are not `Sync`, so the open transaction sits behind a mutex.
+    transaction: Mutex<Option<Transaction<'static, Sqlite>>>,
+}
+
+impl SqliteClient {
+    pub async fn new(options: SqliteConnectOptions) -> anyhow::Result<Self> {
+        // Attached schemas only live on the connection that ran `ATTACH`,
+        // so keep a single connection for the whole session.
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options)
+            .await?;
+
+        Ok(Self {
+            current_schema: RwLock::new(String::from("main")),
+            pool: pool,
+            progress: ScriptProgress::new(),
+            transaction: Mutex::new(None),
+        })
+    }
+
+    fn current_schema(&self) -> String {
+        return self.current_schema.read().unwrap().clone();
+    }
+}
+
+#[async_trait]
+impl SqlClient for SqliteClient {
+    async fn reconnect(&self, new_database: String) -> anyhow::Result<()> {
+        *self.current_schema.write().unwrap() = new_database;
+        Ok(())
+    }
+
+    fn default_database(&self) -> Option<String> {
+        return None;
+    }
+
+    async fn ping(&self) -> anyhow::Result<()> {
+        // The only connection is busy, e.g. holding an import's transaction or running a
+        // script, which would block the ping until it is done. The file is there regardless.
+        if self.pool.size() as usize > self.pool.num_idle() {
+            return Ok(());
+        }
+        self.pool.acquire().await?.ping().await?;
+        Ok(())
+    }
+
+    async fn get_database_list(&self) -> anyhow::Result<Vec<String>> {
+        let get_db_query = "SELECT name FROM pragma_database_list ORDER BY seq";
+        let db_rows = sqlx::query(get_db_query).fetch_all(&self.pool).await?;
+
+        return Ok(parse_sql_db(db_rows));
+    }
+
+    async fn get_table_list(&self, db_name: String) -> anyhow::Result<Vec<String>> {
+        let get_tables_query = format!(
+            "{} {}.sqlite_master {}",
+            r#"SELECT name AS "Name" FROM"#,
+            quote_identifier(&db_name, '"'),
+            "WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' ORDER BY name"
+        );
+        let table_rows = sqlx::query(get_tables_query.as_str())
+            .fetch_all(&self.pool)
+            .await?;
+
+        return Ok(parse_sql_tables(table_rows));
+    }
+
+    async fn get_table_records(
+        &self,
+        table_name: String,
+        filter: Option<&str>,
+        sort: Option<&RecordSort>,
+        key_columns: &[String],
+        limit: usize,
+        offset: usize,
+    ) -> Result<(Vec<String>, Vec<Vec<CellValue>>), sqlx::Error> {
+        let get_records_query = format!(
+            "{} {} {} {} LIMIT {} OFFSET {}",
+            "SELECT * FROM",
+            self.quote_table_name(&table_name),
+            where_clause(filter),
+            order_by_clause(sort, key_columns, '"'),
+            limit,
+            offset
+        );
+        let record_rows = sqlx::query(get_records_query.as_str())
+            .fetch_all(&self.pool)
+            .await?;
+
+        return Ok(parse_sql_table_rows(record_rows));
+    }
+
+    async fn get_table_record_count(
+        &self,
+        table_name: String,
+        filter: Option<&str>,
+    ) -> Result<u64, sqlx::Error> {
+        let get_count_query = format!(
+            "{} {} {}",
+            "SELECT COUNT(*) FROM",
+            self.quote_table_name(&table_name),
+            where_clause(filter)
+        );
+        let count: i64 = sqlx::query_scalar(get_count_query.as_str())
+            .fetch_one(&self.pool)
+            .await?;
+
+        return Ok(count as u64);
+    }
+
+    async fn get_table_columns(
+        &self,
+        table_name: String,
+    ) -> anyhow::Result<(Vec<String>, Vec<Vec<CellValue>>)> {
+        let column_rows = sqlx::query(GET_COLUMNS_QUERY)
+            .bind(table_name)
+            .bind(self.current_schema())
+            .fetch_all(&self.pool)
+            .await?;
+
+        return Ok(parse_sql_table_rows(column_rows));
+    }
+
+    async fn execute_script(
+        &self,
+        statements: &[String],
+        continue_on_error: bool,
+    ) -> Vec<StatementResult> {
+        let _script = self.progress.begin();
+        let mut connection = match self.pool.acquire().await {
+            Ok(connection) => connection,
+            Err(e) => return script_error(statements, e),
+        };
+        let connection_id = None;
+        let mut results = vec![];
+        for (index, sql) in statements.iter().enumerate() {
+            if !self.progress.start(connection_id, index) {
+                break;
+            }
+            let started = Instant::now();
+            let result = run_input_query(&mut *connection, sql).await;
+            let failed = result.is_err();
+            results.push(StatementResult {
+                sql: sql.clone(),
+                result: result,
+                duration: started.elapsed(),
+            });
+            if failed && !continue_on_error {
+                break;
+            }
+        }
+        return results;
+    }
+
+    fn running_statement(&self) -> Option<RunningStatement> {
+        return self.progress.running();
+    }
+
+    async fn cancel_query(&self) -> Result<bool, sqlx::Error> {
+        // SQLite runs in-process and has no session to kill from outside
+        self.progress.cancel();
+        Ok(false)
+    }
+
+    async fn execute_transaction(&self, statements: &[Statement]) -> Result<u64, sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+        let mut rows_affected = 0;
+        for statement in statements.iter() {
+            let mut query = sqlx::query(statement.sql.as_str());
+            for param in statement.params.iter() {
+                query = bind_value(query, param);
+            }
+            rows_affected += query.execute(&mut transaction).await?.rows_affected();
+        }
+        transaction.commit().await?;
+        return Ok(rows_affected);
+    }
+
+    async fn begin_transaction(&self) -> Result<(), sqlx::Error> {
+        *self.transaction.lock().await = Some(self.pool.begin().await?);
+        Ok(())
+    }
+
+    async fn execute_in_transaction(&self, statement: &Statement) -> Result<u64, sqlx::Error> {
+        let mut transaction = self.transaction.lock().await;
+        let transaction = transaction.as_mut().ok_or_else(no_transaction_error)?;
+        let mut query = sqlx::query(statement.sql.as_str());
+        for param in statement.params.iter() {
+            query = bind_value(query, param);
+        }
+        let result = query.execute(transaction).await?;
+        return Ok(result.rows_affected());
+    }
+
+    async fn commit_transaction(&self) -> Result<(), sqlx::Error> {
+        let transaction = self
+            .transaction
+            .lock()
+            .await
+            .take()
+            .ok_or_else(no_transaction_error)?;
+        return transaction.commit().await;
+    }
+
+    async fn rollback_transaction(&self) -> Result<(), sqlx::Error> {
+        let transaction = self
+            .transaction
+            .lock()
+            .await
+            .take()
+            .ok_or_else(no_transaction_error)?;
+        return transaction.rollback().await;
+    }
+
+    fn dialect(&self) -> Dialect {
+        return Dialect::Sqlite;
+    }
+
+    fn quote_identifier(&self, identifier: &str) -> String {
+        return quote_identifier(identifier, '"');
+    }
+
+    fn quote_table_name(&self, table_name: &str) -> String {
+        return format!(
+            "{}.{}",
+            quote_identifier(&self.current_schema(), '"'),
+            quote_identifier(table_name, '"')
+        );
+    }
+
+    fn quote_literal(&self, value: &CellValue) -> String {
+        return non_finite_literal(value, Dialect::Sqlite).unwrap_or_else(|| literal(value, false));
+    }
+
+    fn placeholder(&self, _index: usize, _column_type: &str) -> String {
+        return String::from("?");
+    }
+}
+
+/// Runs one statement from the SQL editor on `executor`. It counts as returning rows if the
+/// driver sent any, or if it is a query that could have.
+async fn run_input_query<'e, E>(executor: E, input: &str) -> Result<QueryResult, sqlx::Error>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let mut stream = sqlx::query(input).fetch_many(executor);
+    let mut rows = vec![];
+    let mut rows_affected = 0;
+    let mut last_insert_id = 0;
+    while let Some(step) = stream.try_next().await? {
+        match step {
+            Either::Left(result) => {
+                rows_affected += result.rows_affected();
+                last_insert_id = result.last_insert_rowid() as u64;
+            }
+            Either::Right(row) => rows.push(row),
+        }
+    }
+    if !rows.is_empty() || is_row_returning_query(input, Dialect::Sqlite) {
+        let (headers, records) = parse_sql_table_rows(rows);
+        return Ok(QueryResult::Rows(headers, records));
+    }
+
+    return Ok(QueryResult::Summary {
+        rows_affected: rows_affected,
+        last_insert_id: Some(last_insert_id),
+    });
+}
+
+fn bind_value<'q>(
+    query: Query<'q, Sqlite, SqliteArguments<'q>>,
+    value: &CellValue,
+) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+    match value.clone() {
+        CellValue::Null => query.bind(None::<String>),
+        CellValue::Bool(value) => query.bind(value),
+        CellValue::Int(value) => query.bind(value),
+        CellValue::UInt(value) => query.bind(value as i64),
+        CellValue::Float(value) => query.bind(value),
+        CellValue::Decimal(value) | CellValue::Text(value) => query.bind(value),
+        CellValue::Bytes(value) => query.bind(value),
+        CellValue::Date(value) => query.bind(value),
+        CellValue::Time(value) => query.bind(value),
+        CellValue::DateTime(value) => query.bind(value),
+        CellValue::Timestamp(value) => query.bind(value),
+        CellValue::Json(value) => query.bind(value.to_string()),
+    }
+}
@@ -0,0 +1,211 @@
+use crate::model::cell::CellValue;
+
+use super::{lexer::Dialect, sql_client::SqlClient};
+
+/// A column value together with the column's declared type, used to build placeholders.
+#[derive(Clone)]
+pub struct ColumnValue {
+    pub column: String,
+    pub column_type: String,
+    pub value: CellValue,
+}
+
+/// A parameterized statement plus the values bound to its placeholders.
+#[derive(Clone)]
+pub struct Statement {
+    pub sql: String,
+    pub params: Vec<CellValue>,
+}
+
+impl Statement {
+    pub fn update(
+        sql_client: &dyn SqlClient,
+        table: &str,
+        assignment: &ColumnValue,
+        primary_key: &[ColumnValue],
+    ) -> Self {
+        let mut statement = Self {
+            sql: format!(
+                "UPDATE {} SET {} = {}",
+                sql_client.quote_table_name(table),
+                sql_client.quote_identifier(&assignment.column),
+                sql_client.placeholder(1, &assignment.column_type)
+            ),
+            params: vec![assignment.value.clone()],
+        };
+        statement.push_primary_key_condition(sql_client, primary_key);
+        return statement;
+    }
+
+    /// Inserts `values`; columns left out fall back to their defaults.
+    pub fn insert(sql_client: &dyn SqlClient, table: &str, values: &[ColumnValue]) -> Self {
+        return Self::insert_rows(sql_client, table, &[values.to_vec()]);
+    }
+
+    /// One multi-row INSERT. Every row must list the same columns as the first.
+    pub fn insert_rows(sql_client: &dyn SqlClient, table: &str, rows: &[Vec<ColumnValue>]) -> Self {
+        let first_row = rows.first().map(|row| row.as_slice()).unwrap_or_default();
+        let columns: Vec<String> = first_row
+            .iter()
+            .map(|value| sql_client.quote_identifier(&value.column))
+            .collect();
+        let mut params = vec![];
+        let mut tuples = vec![];
+        for row in rows.iter() {
+            let placeholders: Vec<String> = row
+                .iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    sql_client.placeholder(params.len() + index + 1, &value.column_type)
+                })
+                .collect();
+            tuples.push(format!("({})", placeholders.join(", ")));
+            params.extend(row.iter().map(|value| value.value.clone()));
+        }
+        return Self {
+            sql: format!(
+                "INSERT INTO {} ({}) VALUES {}",
+                sql_client.quote_table_name(table),
+                columns.join(", "),
+                tuples.join(", ")
+            ),
+            params: params,
+        };
+    }
+
+    /// A statement without bound values, e.g. `SAVEPOINT import`.
+    pub fn raw(sql: &str) -> Self {
+        return Self {
+            sql: sql.to_string(),
+            params: vec![],
+        };
+    }
+
+    pub fn delete(sql_client: &dyn SqlClient, table: &str, primary_key: &[ColumnValue]) -> Self {
+        let mut statement = Self {
+            sql: format!("DELETE FROM {}", sql_client.quote_table_name(table)),
+            params: vec![],
+        };
+        statement.push_primary_key_condition(sql_client, primary_key);
+        return statement;
+    }
+
+    fn push_primary_key_condition(
+        &mut self,
+        sql_client: &dyn SqlClient,
+        primary_key: &[ColumnValue],
+    ) {
+        let conditions: Vec<String> = primary_key
+            .iter()
+            .enumerate()
+            .map(|(index, key)| {
+                format!(
+                    "{} = {}",
+                    sql_client.quote_identifier(&key.column),
+                    sql_client.placeholder(self.params.len() + index + 1, &key.column_type)
+                )
+            })
+            .collect();
+        self.sql = format!("{} WHERE {}", self.sql, conditions.join(" AND "));
+        self.params
+            .extend(primary_key.iter().map(|key| key.value.clone()));
+    }
+
+    /// The statement followed by its bound values, as shown for confirmation.
+    pub fn preview(&self) -> String {
+        let params: Vec<String> = self
+            .params
+            .iter()
+            .map(|value| literal(value, false))
+            .collect();
+        return format!("{};\n-- params: {}", self.sql, params.join(", "));
+    }
+}
+
+/// `value` written as an SQL literal. With `escape_backslash`, as MySQL reads strings by
+/// default, backslashes in strings are escaped as well as quotes. NaN and infinite numbers
+/// are written as Postgres reads them, see `non_finite_literal` for the other backends.
+pub fn literal(value: &CellValue, escape_backslash: bool) -> String {
+    match value {
+        CellValue::Null => String::from("NULL"),
+        value if value.is_numeric() => {
+            non_finite_literal(value, Dialect::Postgres).unwrap_or_else(|| value.to_string())
+        }
+        CellValue::Bool(_) => value.to_string(),
+        CellValue::Bytes(_) => format!("X'{}'", value.to_string().trim_start_matches("0x")),
+        value => {
+            let mut text = value.to_string();
+            if escape_backslash {
+                text = text.replace('\\', "\\\\");
+            }
+            format!("'{}'", text.replace('\'', "''"))
+        }
+    }
+}
+
+/// NaN or an infinite number written as `dialect` reads it back, None for other values.
+/// None is a number literal: Postgres reads them from strings, SQLite reads infinity
+/// from an overflowing number, and the rest becomes NULL with a comment for a warning.
+pub fn non_finite_literal(value: &CellValue, dialect: Dialect) -> Option<String> {
+    let name = match value {
+        CellValue::Float(value) if value.is_nan() => "NaN",
+        CellValue::Float(value) if *value == f64::INFINITY => "Infinity",
+        CellValue::Float(value) if *value == f64::NEG_INFINITY => "-Infinity",
+        CellValue::Decimal(value) if ["NaN", "Infinity", "-Infinity"].contains(&value.as_str()) => {
+            value.as_str()
+        }
+        _ => return None,
+    };
+    let literal = match (dialect, name) {
+        (Dialect::Postgres, name) => format!("'{}'", name),
+        (Dialect::Sqlite, "Infinity") => String::from("9e999"),
+        (Dialect::Sqlite, "-Infinity") => String::from("-9e999"),
+        (_, name) => format!("NULL /* {} is not supported */", name),
+    };
+    return Some(literal);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{literal, non_finite_literal};
+    use crate::{db::lexer::Dialect, model::cell::CellValue};
+
+    #[test]
+    fn quotes_strings() {
+        let value = CellValue::Text(String::from("it's"));
+        assert_eq!(literal(&value, false), "'it''s'");
+        assert_eq!(literal(&CellValue::Null, false), "NULL");
+        assert_eq!(literal(&CellValue::Int(-3), false), "-3");
+        assert_eq!(literal(&CellValue::Bytes(vec![0, 255]), false), "X'00ff'");
+    }
+
+    #[test]
+    fn escapes_backslashes_for_mysql() {
+        let value = CellValue::Text(String::from("foo\\', 1); --"));
+        assert_eq!(literal(&value, true), "'foo\\\\'', 1); --'");
+        assert_eq!(literal(&value, false), "'foo\\'', 1); --'");
+    }
+
+    #[test]
+    fn writes_non_finite_numbers_per_dialect() {
+        let nan = CellValue::Float(f64::NAN);
+        let infinity = CellValue::Float(f64::NEG_INFINITY);
+        assert_eq!(literal(&nan, false), "'NaN'");
+        assert_eq!(
+            literal(&CellValue::Decimal(String::from("Infinity")), false),
+            "'Infinity'"
+        );
+        assert_eq!(
+            non_finite_literal(&infinity, Dialect::Sqlite).as_deref(),
+            Some("-9e999")
+        );
+        assert_eq!(
+            non_finite_literal(&nan, Dialect::MySql).as_deref(),
+            Some("NULL /* NaN is not supported */")
+        );
+        assert_eq!(
+            non_finite_literal(&CellValue::Float(1.5), Dialect::MySql),
+            None
+        );
+    }
+}
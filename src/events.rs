@@ -0,0 +1,383 @@
+use std::{collections::VecDeque, future::Future, sync::Arc, time::Duration};
+
+use crossterm::event::{Event, EventStream};
+use futures_util::StreamExt;
+use tokio::{
+    signal::unix::{signal, SignalKind},
+    sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    task::JoinHandle,
+};
+
+use crate::{
+    db::sql_client::SqlClient,
+    import::ImportOutcome,
+    model::{
+        cell::CellValue,
+        database::DatabaseModel,
+        table::{TableModel, TableRecordModel},
+    },
+};
+
+/// How often a `Tick` arrives, which redraws the screen so spinners move.
+const TICK_RATE: Duration = Duration::from_millis(250);
+/// How often the connection is checked while it works.
+const HEARTBEAT_RATE: Duration = Duration::from_secs(5);
+/// A ping not answered by then counts as failed, the pool would keep trying for longer.
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
+/// The wait before the first reconnect attempt, doubled after each failed one up to the max.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Everything the main loop reacts to, merged into one channel.
+pub enum AppEvent {
+    Input(Event),
+    Tick,
+    /// A background load started by `Loader::start` is done.
+    Loaded {
+        load_id: u64,
+        result: LoadResult,
+    },
+    /// How far the load `load_id` got with `action`, e.g. rows imported out of all rows.
+    Progress {
+        load_id: u64,
+        action: &'static str,
+        done: usize,
+        total: usize,
+    },
+    /// Column names of `table` in `database`, looked up for completion.
+    Columns {
+        database: String,
+        table: String,
+        columns: Vec<String>,
+    },
+    Connection(ConnectionState),
+    /// SIGINT or SIGTERM: quit the way `q` does.
+    Terminate,
+    /// SIGTSTP from outside, e.g. `kill -TSTP`: suspend the way Ctrl-Z does.
+    Suspend,
+}
+
+pub enum ConnectionState {
+    /// Reconnect attempt `attempt` failed, or the server stopped answering if it is 0.
+    Lost {
+        attempt: u32,
+        error: String,
+        retry_in: Duration,
+    },
+    Restored,
+}
+
+pub enum LoadResult {
+    Table(Box<TableLoad>),
+    /// The connection to the config file's `index`th profile, with its database opened.
+    Profile {
+        index: usize,
+        connection: anyhow::Result<(Arc<dyn SqlClient>, DatabaseModel)>,
+    },
+    /// The database switched to, with its tables if the switch worked.
+    Database {
+        database: String,
+        tables: anyhow::Result<Vec<String>>,
+    },
+    /// The shown table's first page, loaded again for another sort or filter.
+    Records {
+        table: String,
+        records: Result<TableRecordModel, sqlx::Error>,
+    },
+    /// The page of `table` fetched from row `offset` on.
+    Page {
+        table: String,
+        offset: usize,
+        page: Result<(Vec<String>, Vec<Vec<CellValue>>), sqlx::Error>,
+    },
+    /// The reviewed changes, committed with the rows they affected or rolled back.
+    Committed {
+        rows_affected: Result<u64, sqlx::Error>,
+    },
+    Import(Result<ImportOutcome, sqlx::Error>),
+    /// The rows written to `path` by an export.
+    Export {
+        path: String,
+        rows: anyhow::Result<usize>,
+    },
+}
+
+pub struct TableLoad {
+    pub table: String,
+    pub model: anyhow::Result<TableModel>,
+    /// Why the table's remembered filter was dropped, if it no longer applied.
+    pub filter_error: Option<anyhow::Error>,
+}
+
+/// The event channel, fed by a terminal input task, a ticker, background loads, the
+/// connection watcher and signals.
+pub struct Events {
+    sender: UnboundedSender<AppEvent>,
+    receiver: UnboundedReceiver<AppEvent>,
+    deferred: VecDeque<AppEvent>,
+    input: Option<JoinHandle<()>>,
+    watcher: Option<JoinHandle<()>>,
+}
+
+impl Events {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let tick_sender = sender.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(TICK_RATE);
+            loop {
+                interval.tick().await;
+                if tick_sender.send(AppEvent::Tick).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut events = Self {
+            sender: sender,
+            receiver: receiver,
+            deferred: VecDeque::new(),
+            input: None,
+            watcher: None,
+        };
+        events.resume_input();
+        events.watch_signals();
+        return events;
+    }
+
+    /// The next event, starting with those put back by `defer`.
+    pub async fn next(&mut self) -> AppEvent {
+        if let Some(event) = self.deferred.pop_front() {
+            return event;
+        }
+        return self.receive().await;
+    }
+
+    /// The next event from the channel, skipping those put back by `defer`.
+    pub async fn receive(&mut self) -> AppEvent {
+        // `self.sender` keeps the channel open, so there is always another event
+        return self.receiver.recv().await.unwrap();
+    }
+
+    /// Puts back an event that arrived while something else was waiting, for `next` to return.
+    pub fn defer(&mut self, event: AppEvent) {
+        self.deferred.push_back(event);
+    }
+
+    /// Runs `task` in the background beside the `Loader`'s load and sends the event it ends with.
+    pub fn spawn<F>(&self, task: F)
+    where
+        F: Future<Output = AppEvent> + Send + 'static,
+    {
+        let sender = self.sender.clone();
+        tokio::spawn(async move {
+            let _ = sender.send(task.await);
+        });
+    }
+
+    fn watch_signals(&self) {
+        let sender = self.sender.clone();
+        tokio::spawn(async move {
+            let (mut interrupt, mut terminate, mut suspend) = match (
+                signal(SignalKind::interrupt()),
+                signal(SignalKind::terminate()),
+                signal(SignalKind::from_raw(libc::SIGTSTP)),
+            ) {
+                (Ok(interrupt), Ok(terminate), Ok(suspend)) => (interrupt, terminate, suspend),
+                _ => return,
+            };
+            loop {
+                let event = tokio::select! {
+                    _ = interrupt.recv() => AppEvent::Terminate,
+                    _ = terminate.recv() => AppEvent::Terminate,
+                    _ = suspend.recv() => AppEvent::Suspend,
+                };
+                if sender.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Pings the server in the background. Once a ping fails, it is retried with a growing
+    /// delay until one goes through, reporting each attempt as a `Connection` event.
+    /// Stops watching the client watched before, and drops what it reported but was not seen yet.
+    pub fn watch_connection(&mut self, sql_client: Arc<dyn SqlClient>) {
+        if let Some(watcher) = self.watcher.take() {
+            watcher.abort();
+        }
+        self.deferred
+            .retain(|event| !matches!(event, AppEvent::Connection(_)));
+        let sender = self.sender.clone();
+        self.watcher = Some(tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(HEARTBEAT_RATE).await;
+                let mut error = match ping(sql_client.as_ref()).await {
+                    Ok(()) => continue,
+                    Err(e) => e,
+                };
+                let mut attempt = 0;
+                let mut retry_in = RECONNECT_DELAY;
+                loop {
+                    let lost = ConnectionState::Lost {
+                        attempt: attempt,
+                        error: error.to_string(),
+                        retry_in: retry_in,
+                    };
+                    if sender.send(AppEvent::Connection(lost)).is_err() {
+                        return;
+                    }
+                    tokio::time::sleep(retry_in).await;
+                    match ping(sql_client.as_ref()).await {
+                        Ok(()) => break,
+                        Err(e) => error = e,
+                    }
+                    attempt += 1;
+                    retry_in = (retry_in * 2).min(MAX_RECONNECT_DELAY);
+                }
+                if sender
+                    .send(AppEvent::Connection(ConnectionState::Restored))
+                    .is_err()
+                {
+                    return;
+                }
+            }
+        }));
+    }
+
+    /// Stops reading the terminal, so a program run in it gets all of the input.
+    pub async fn pause_input(&mut self) {
+        if let Some(input) = self.input.take() {
+            input.abort();
+            // Wait for the stream to be dropped, which stops its reader thread
+            let _ = input.await;
+        }
+    }
+
+    pub fn resume_input(&mut self) {
+        if self.input.is_some() {
+            return;
+        }
+        let sender = self.sender.clone();
+        self.input = Some(tokio::spawn(async move {
+            let mut stream = EventStream::new();
+            while let Some(event) = stream.next().await {
+                // A read interrupted by the previous stream shutting down is not fatal
+                if let Ok(event) = event {
+                    if sender.send(AppEvent::Input(event)).is_err() {
+                        break;
+                    }
+                }
+            }
+        }));
+    }
+}
+
+async fn ping(sql_client: &dyn SqlClient) -> anyhow::Result<()> {
+    return match tokio::time::timeout(PING_TIMEOUT, sql_client.ping()).await {
+        Ok(result) => result,
+        Err(_) => Err(anyhow::anyhow!(
+            "The server did not answer within {}s",
+            PING_TIMEOUT.as_secs()
+        )),
+    };
+}
+
+/// The background load the screen is waiting for. Starting another one or cancelling
+/// aborts it, and a result it already sent is recognised as stale by its id.
+pub struct Loader {
+    load_id: u64,
+    task: Option<JoinHandle<()>>,
+}
+
+impl Loader {
+    pub fn new() -> Self {
+        Self {
+            load_id: 0,
+            task: None,
+        }
+    }
+
+    pub fn start<F>(&mut self, events: &Events, load: F)
+    where
+        F: Future<Output = LoadResult> + Send + 'static,
+    {
+        self.cancel();
+        self.spawn(events, load);
+    }
+
+    /// Like `start`, handing the load a `Progress` to report how far it got with `action`,
+    /// e.g. `Importing`.
+    pub fn start_with_progress<F, L>(&mut self, events: &Events, action: &'static str, load: L)
+    where
+        L: FnOnce(Progress) -> F,
+        F: Future<Output = LoadResult> + Send + 'static,
+    {
+        self.cancel();
+        let progress = Progress {
+            load_id: self.load_id,
+            action: action,
+            sender: events.sender.clone(),
+        };
+        self.spawn(events, load(progress));
+    }
+
+    fn spawn<F>(&mut self, events: &Events, load: F)
+    where
+        F: Future<Output = LoadResult> + Send + 'static,
+    {
+        let load_id = self.load_id;
+        let sender = events.sender.clone();
+        self.task = Some(tokio::spawn(async move {
+            let result = load.await;
+            let _ = sender.send(AppEvent::Loaded {
+                load_id: load_id,
+                result: result,
+            });
+        }));
+    }
+
+    pub fn cancel(&mut self) {
+        self.load_id += 1;
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+
+    /// Whether a load is still running.
+    pub fn is_busy(&self) -> bool {
+        return self.task.is_some();
+    }
+
+    /// Whether `load_id` is the load still running.
+    pub fn is_current(&self, load_id: u64) -> bool {
+        return load_id == self.load_id && self.is_busy();
+    }
+
+    /// Whether `load_id` is the load still waited for, which is then done.
+    pub fn finish(&mut self, load_id: u64) -> bool {
+        if load_id != self.load_id {
+            return false;
+        }
+        self.task = None;
+        return true;
+    }
+}
+
+/// Sends `AppEvent::Progress` for the load it was handed to.
+pub struct Progress {
+    load_id: u64,
+    action: &'static str,
+    sender: UnboundedSender<AppEvent>,
+}
+
+impl Progress {
+    pub fn report(&self, done: usize, total: usize) {
+        let _ = self.sender.send(AppEvent::Progress {
+            load_id: self.load_id,
+            action: self.action,
+            done: done,
+            total: total,
+        });
+    }
+}
@@ -0,0 +1,459 @@
+use std::{
+    fmt,
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    db::sql_client::SqlClient,
+    model::{
+        cell::CellValue,
+        table::{TableRecordModel, RECORD_PAGE_SIZE},
+    },
+};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    Markdown,
+    Insert,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::Markdown => "md",
+            ExportFormat::Insert => "sql",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            ExportFormat::Csv => ExportFormat::Json,
+            ExportFormat::Json => ExportFormat::Markdown,
+            ExportFormat::Markdown => ExportFormat::Insert,
+            ExportFormat::Insert => ExportFormat::Csv,
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportFormat::Csv => write!(f, "CSV"),
+            ExportFormat::Json => write!(f, "JSON"),
+            ExportFormat::Markdown => write!(f, "Markdown"),
+            ExportFormat::Insert => write!(f, "INSERT INTO"),
+        }
+    }
+}
+
+/// Writes the loaded records of `model` to `path` and returns how many rows were written.
+/// `table` names the target of the generated INSERT statements. An existing file at `path`
+/// is only replaced with `overwrite`, otherwise this fails with `AlreadyExists`.
+pub fn export(
+    sql_client: &dyn SqlClient,
+    model: &TableRecordModel,
+    table: &str,
+    format: ExportFormat,
+    path: &Path,
+    overwrite: bool,
+) -> io::Result<usize> {
+    let mut writer = ExportWriter::create(path, overwrite, format, model.headers.clone(), table)?;
+    writer.write_page(sql_client, &model.records)?;
+    return writer.finish();
+}
+
+/// Writes every record of `table`, filtered and sorted like `view`, to `path` a page at a
+/// time, so the table is never held in memory. `progress` gets the rows written so far and
+/// the total `view` counted. Returns how many rows were written. `overwrite` is as for `export`.
+pub async fn export_table(
+    sql_client: &dyn SqlClient,
+    table: &str,
+    view: &TableRecordModel,
+    format: ExportFormat,
+    path: &Path,
+    overwrite: bool,
+    progress: impl Fn(usize, usize),
+) -> anyhow::Result<usize> {
+    let mut writer = ExportWriter::create(path, overwrite, format, view.headers.clone(), table)?;
+    let total = view.total as usize;
+    loop {
+        let (_, records) = sql_client
+            .get_table_records(
+                table.to_string(),
+                view.filter.as_deref(),
+                view.sort.as_ref(),
+                &view.key_columns,
+                RECORD_PAGE_SIZE,
+                writer.rows,
+            )
+            .await?;
+        writer.write_page(sql_client, &records)?;
+        progress(writer.rows, total.max(writer.rows));
+        if records.len() < RECORD_PAGE_SIZE {
+            break;
+        }
+    }
+    return Ok(writer.finish()?);
+}
+
+/// A file being written in one of the formats a page of records at a time.
+/// Markdown aligns its columns by what the first page holds.
+struct ExportWriter {
+    file: BufWriter<File>,
+    format: ExportFormat,
+    headers: Vec<String>,
+    /// What the generated INSERT statements insert into.
+    table: String,
+    rows: usize,
+    started: bool,
+}
+
+impl ExportWriter {
+    fn create(
+        path: &Path,
+        overwrite: bool,
+        format: ExportFormat,
+        headers: Vec<String>,
+        table: &str,
+    ) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(!overwrite)
+            .create(overwrite)
+            .truncate(overwrite)
+            .open(path)?;
+        Ok(Self {
+            file: BufWriter::new(file),
+            format: format,
+            headers: headers,
+            table: table.to_string(),
+            rows: 0,
+            started: false,
+        })
+    }
+
+    fn write_page(
+        &mut self,
+        sql_client: &dyn SqlClient,
+        records: &[Vec<CellValue>],
+    ) -> io::Result<()> {
+        let mut content = String::new();
+        if !self.started {
+            content.push_str(&self.start(records));
+            self.started = true;
+        }
+        for record in records.iter() {
+            let text = match self.format {
+                ExportFormat::Csv => csv_record(record) + "\r\n",
+                ExportFormat::Json if self.rows == 0 => {
+                    format!("[\n{}", json_object(&self.headers, record))
+                }
+                ExportFormat::Json => format!(",\n{}", json_object(&self.headers, record)),
+                ExportFormat::Markdown => markdown_record(record) + "\n",
+                ExportFormat::Insert => {
+                    insert_statement(sql_client, &self.table, &self.headers, record)
+                }
+            };
+            content.push_str(&text);
+            self.rows += 1;
+        }
+        return self.file.write_all(content.as_bytes());
+    }
+
+    /// What comes before the first record, which for Markdown depends on `records`.
+    fn start(&self, records: &[Vec<CellValue>]) -> String {
+        return match self.format {
+            ExportFormat::Csv => {
+                csv_line(self.headers.iter().map(|header| csv_field(header))) + "\r\n"
+            }
+            ExportFormat::Markdown => markdown_header(&self.headers, records),
+            ExportFormat::Json | ExportFormat::Insert => String::new(),
+        };
+    }
+
+    /// Ends the file and returns how many rows were written.
+    fn finish(mut self) -> io::Result<usize> {
+        let mut content = String::new();
+        if !self.started {
+            content.push_str(&self.start(&[]));
+        }
+        if self.format == ExportFormat::Json {
+            content.push_str(if self.rows == 0 { "[]\n" } else { "\n]\n" });
+        }
+        self.file.write_all(content.as_bytes())?;
+        self.file.flush()?;
+        return Ok(self.rows);
+    }
+}
+
+/// RFC 4180 CSV. NULL is written as an empty field.
+pub fn to_csv(headers: &[String], records: &[Vec<CellValue>]) -> String {
+    let mut lines = vec![csv_line(headers.iter().map(|header| csv_field(header)))];
+    lines.extend(records.iter().map(|record| csv_record(record)));
+    return lines.join("\r\n") + "\r\n";
+}
+
+fn csv_record(record: &[CellValue]) -> String {
+    return csv_line(record.iter().map(|value| match value {
+        CellValue::Null => String::new(),
+        value => csv_field(&value.to_string()),
+    }));
+}
+
+fn csv_line(fields: impl Iterator<Item = String>) -> String {
+    return fields.collect::<Vec<String>>().join(",");
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) || field.starts_with(' ') || field.ends_with(' ') {
+        return format!("\"{}\"", field.replace('"', "\"\""));
+    }
+    return field.to_string();
+}
+
+/// A JSON array with one object per record, keys in column order.
+pub fn to_json(headers: &[String], records: &[Vec<CellValue>]) -> String {
+    let objects: Vec<String> = records
+        .iter()
+        .map(|record| json_object(headers, record))
+        .collect();
+    if objects.is_empty() {
+        return String::from("[]\n");
+    }
+    return format!("[\n{}\n]\n", objects.join(",\n"));
+}
+
+fn json_object(headers: &[String], record: &[CellValue]) -> String {
+    let members: Vec<String> = headers
+        .iter()
+        .zip(record.iter())
+        .map(|(header, value)| {
+            format!(
+                "{}: {}",
+                serde_json::Value::String(header.clone()),
+                json_value(value)
+            )
+        })
+        .collect();
+    return format!("  {{{}}}", members.join(", "));
+}
+
+fn json_value(value: &CellValue) -> serde_json::Value {
+    match value {
+        CellValue::Null => serde_json::Value::Null,
+        CellValue::Bool(value) => serde_json::Value::from(*value),
+        CellValue::Int(value) => serde_json::Value::from(*value),
+        CellValue::UInt(value) => serde_json::Value::from(*value),
+        CellValue::Float(value) => serde_json::Number::from_f64(*value)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        CellValue::Json(value) => value.clone(),
+        // Decimals stay strings so no precision is lost.
+        value => serde_json::Value::String(value.to_string()),
+    }
+}
+
+/// A table framed like the mysql client prints it, with numbers right-aligned.
+pub fn to_text_table(headers: &[String], records: &[Vec<CellValue>]) -> String {
+    let rows: Vec<Vec<String>> = records
+        .iter()
+        .map(|record| record.iter().map(|value| value.to_string()).collect())
+        .collect();
+    let widths: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(index, header)| {
+            rows.iter()
+                .filter_map(|row| row.get(index))
+                .map(|text| text.width())
+                .chain([header.width()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let border = format!(
+        "+{}+",
+        widths
+            .iter()
+            .map(|width| "-".repeat(width + 2))
+            .collect::<Vec<String>>()
+            .join("+")
+    );
+    let line = |cells: Vec<String>| format!("| {} |", cells.join(" | "));
+
+    let mut lines = vec![border.clone()];
+    lines.push(line(
+        headers
+            .iter()
+            .zip(widths.iter())
+            .map(|(header, width)| pad(header, *width, false))
+            .collect(),
+    ));
+    lines.push(border.clone());
+    for (record, row) in records.iter().zip(rows.iter()) {
+        lines.push(line(
+            record
+                .iter()
+                .zip(row.iter())
+                .zip(widths.iter())
+                .map(|((value, text), width)| pad(text, *width, value.is_numeric()))
+                .collect(),
+        ));
+    }
+    lines.push(border);
+    return lines.join("\n") + "\n";
+}
+
+fn pad(text: &str, width: usize, right_align: bool) -> String {
+    let padding = " ".repeat(width.saturating_sub(text.width()));
+    if right_align {
+        return padding + text;
+    }
+    return text.to_string() + &padding;
+}
+
+/// The head of a GitHub flavoured Markdown table, with the columns right-aligned that
+/// only hold numbers in `records`.
+fn markdown_header(headers: &[String], records: &[Vec<CellValue>]) -> String {
+    let alignments: Vec<&str> = (0..headers.len())
+        .map(|index| {
+            let is_numeric = records
+                .iter()
+                .filter_map(|record| record.get(index))
+                .filter(|value| !value.is_null())
+                .all(|value| value.is_numeric());
+            if is_numeric && !records.is_empty() {
+                "---:"
+            } else {
+                "---"
+            }
+        })
+        .collect();
+    return format!(
+        "{}\n{}\n",
+        markdown_row(headers.iter().map(|header| markdown_cell(header))),
+        markdown_row(alignments.iter().map(|alignment| alignment.to_string()))
+    );
+}
+
+fn markdown_record(record: &[CellValue]) -> String {
+    return markdown_row(record.iter().map(|value| markdown_cell(&value.to_string())));
+}
+
+fn markdown_row(cells: impl Iterator<Item = String>) -> String {
+    return format!("| {} |", cells.collect::<Vec<String>>().join(" | "));
+}
+
+fn markdown_cell(text: &str) -> String {
+    return text
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>");
+}
+
+/// An `INSERT INTO` statement for one record.
+fn insert_statement(
+    sql_client: &dyn SqlClient,
+    table: &str,
+    headers: &[String],
+    record: &[CellValue],
+) -> String {
+    let columns: Vec<String> = headers
+        .iter()
+        .map(|header| sql_client.quote_identifier(header))
+        .collect();
+    let values: Vec<String> = record
+        .iter()
+        .map(|value| sql_client.quote_literal(value))
+        .collect();
+    return format!(
+        "INSERT INTO {} ({}) VALUES ({});\n",
+        sql_client.quote_table_name(table),
+        columns.join(", "),
+        values.join(", ")
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, io, process};
+
+    use super::{to_csv, to_json, to_text_table, ExportFormat, ExportWriter};
+    use crate::model::cell::CellValue;
+
+    fn headers() -> Vec<String> {
+        return vec![String::from("id"), String::from("note")];
+    }
+
+    #[test]
+    fn csv_quotes_fields_that_need_it() {
+        let records = vec![
+            vec![CellValue::Int(1), CellValue::Text(String::from("a,\"b\""))],
+            vec![CellValue::Int(2), CellValue::Null],
+            vec![
+                CellValue::Int(3),
+                CellValue::Text(String::from(" line\nbreak")),
+            ],
+        ];
+        assert_eq!(
+            to_csv(&headers(), &records),
+            "id,note\r\n1,\"a,\"\"b\"\"\"\r\n2,\r\n3,\" line\nbreak\"\r\n"
+        );
+    }
+
+    #[test]
+    fn json_keeps_types_and_column_order() {
+        let records = vec![vec![
+            CellValue::Int(1),
+            CellValue::Text(String::from("say \"hi\"")),
+        ]];
+        assert_eq!(
+            to_json(&headers(), &records),
+            "[\n  {\"id\": 1, \"note\": \"say \\\"hi\\\"\"}\n]\n"
+        );
+        assert_eq!(to_json(&headers(), &[]), "[]\n");
+    }
+
+    #[test]
+    fn text_table_pads_by_display_width() {
+        let records = vec![
+            vec![CellValue::Int(7), CellValue::Text(String::from("日本"))],
+            vec![CellValue::Int(1200), CellValue::Null],
+        ];
+        assert_eq!(
+            to_text_table(&headers(), &records),
+            "+------+------+\n\
+             | id   | note |\n\
+             +------+------+\n\
+             |    7 | 日本 |\n\
+             | 1200 | NULL |\n\
+             +------+------+\n"
+        );
+    }
+
+    #[test]
+    fn replaces_an_existing_file_only_when_asked() {
+        let path = env::temp_dir().join(format!("sql-cli-rust-export-{}.csv", process::id()));
+        fs::write(&path, "old").unwrap();
+        let error = ExportWriter::create(&path, false, ExportFormat::Csv, headers(), "t")
+            .err()
+            .unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "old");
+
+        ExportWriter::create(&path, true, ExportFormat::Csv, headers(), "t")
+            .unwrap()
+            .finish()
+            .unwrap();
+        assert_ne!(fs::read_to_string(&path).unwrap(), "old");
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,121 @@
+use std::{
+    env,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// One statement run from the SQL editor, stored as a line of JSON in the history file.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub timestamp: DateTime<Local>,
+    pub database: String,
+    pub duration_ms: u64,
+    pub success: bool,
+    pub sql: String,
+}
+
+/// Every query run so far, oldest first, and where Up/Down walking currently is.
+pub struct QueryHistory {
+    path: Option<PathBuf>,
+    pub entries: Vec<HistoryEntry>,
+    position: Option<usize>,
+    draft: String,
+}
+
+impl QueryHistory {
+    /// Reads the history file, skipping lines that do not parse.
+    /// `HISTORY_FILE` overrides the default location in the user's data directory.
+    pub fn load() -> Self {
+        let path = env::var("HISTORY_FILE")
+            .ok()
+            .map(PathBuf::from)
+            .or_else(|| dirs::data_dir().map(|dir| dir.join("sql-cli-rust").join("history.jsonl")));
+        let entries = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|content| {
+                content
+                    .lines()
+                    .filter_map(|line| serde_json::from_str(line).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            path: path,
+            entries: entries,
+            position: None,
+            draft: String::new(),
+        }
+    }
+
+    /// Appends `entry` to the history file and ends any Up/Down walk.
+    pub fn record(&mut self, entry: HistoryEntry) -> io::Result<()> {
+        self.position = None;
+        let line = serde_json::to_string(&entry)?;
+        self.entries.push(entry);
+
+        let path = match self.path.as_ref() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", line)?;
+        return Ok(());
+    }
+
+    /// The query before the one shown, skipping repeats of `current`.
+    /// Walking starts from the newest entry and remembers `current` as the draft to come back to.
+    pub fn previous(&mut self, current: &str) -> Option<&str> {
+        let mut position = match self.position {
+            Some(position) => position,
+            None => {
+                self.draft = current.to_string();
+                self.entries.len()
+            }
+        };
+        while position > 0 {
+            position -= 1;
+            if self.entries[position].sql != current {
+                self.position = Some(position);
+                return Some(&self.entries[position].sql);
+            }
+        }
+        return None;
+    }
+
+    /// The query after the one shown, or the draft once past the newest entry.
+    pub fn next(&mut self, current: &str) -> Option<&str> {
+        let mut position = self.position?;
+        while position + 1 < self.entries.len() {
+            position += 1;
+            if self.entries[position].sql != current {
+                self.position = Some(position);
+                return Some(&self.entries[position].sql);
+            }
+        }
+        self.position = None;
+        return Some(&self.draft);
+    }
+
+    /// Distinct queries containing `query`, ignoring case, newest first.
+    pub fn search(&self, query: &str) -> Vec<&HistoryEntry> {
+        let query = query.to_lowercase();
+        let mut matches: Vec<&HistoryEntry> = vec![];
+        for entry in self.entries.iter().rev() {
+            if entry.sql.to_lowercase().contains(&query)
+                && !matches.iter().any(|found| found.sql == entry.sql)
+            {
+                matches.push(entry);
+            }
+        }
+        return matches;
+    }
+}
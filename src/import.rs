@@ -0,0 +1,470 @@
+use std::fs;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime};
+
+use crate::{
+    db::{
+        sql_client::SqlClient,
+        statement::{ColumnValue, Statement},
+    },
+    model::{
+        cell::CellValue,
+        table::{base_type, ColumnDefinition},
+    },
+};
+
+/// Rows inserted per multi-row INSERT.
+const IMPORT_BATCH_SIZE: usize = 100;
+
+/// Guards each batch so a failing row can be undone without aborting the whole import.
+const SAVEPOINT: &str = "csv_import";
+
+/// Column types by their `base_type` name, across MySQL, Postgres and SQLite.
+const BOOLEAN_TYPES: &[&str] = &["bool", "boolean"];
+const INTEGER_TYPES: &[&str] = &[
+    "int",
+    "integer",
+    "tinyint",
+    "smallint",
+    "mediumint",
+    "bigint",
+    "int2",
+    "int4",
+    "int8",
+    "serial",
+    "smallserial",
+    "bigserial",
+    "serial4",
+    "serial8",
+];
+const FLOAT_TYPES: &[&str] = &[
+    "real",
+    "float",
+    "float4",
+    "float8",
+    "double",
+    "double precision",
+];
+const DECIMAL_TYPES: &[&str] = &["decimal", "dec", "numeric", "fixed"];
+const DATETIME_TYPES: &[&str] = &[
+    "datetime",
+    "timestamp",
+    "timestamptz",
+    "timestamp with time zone",
+    "timestamp without time zone",
+];
+const TIME_TYPES: &[&str] = &[
+    "time",
+    "timetz",
+    "time with time zone",
+    "time without time zone",
+];
+
+/// Why a CSV data row (1-based, header not counted) was not imported.
+pub struct RowError {
+    pub row: usize,
+    pub message: String,
+}
+
+/// A CSV file read into memory and matched against the columns of `table`.
+pub struct ImportPlan {
+    pub path: String,
+    pub table: String,
+    pub headers: Vec<String>,
+    /// Each table column with the index of the CSV column that fills it, if any.
+    pub mapping: Vec<(ColumnDefinition, Option<usize>)>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl ImportPlan {
+    /// Reads `path` and maps its header row onto `columns` by name, ignoring case.
+    pub fn new(path: &str, table: &str, columns: Vec<ColumnDefinition>) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        let mut records = parse_csv(content.trim_start_matches('\u{feff}'))?;
+        if records.is_empty() {
+            return Err(format!("{} has no header row", path));
+        }
+        let headers = records.remove(0);
+        let mapping: Vec<(ColumnDefinition, Option<usize>)> = columns
+            .into_iter()
+            .map(|column| {
+                let index = headers
+                    .iter()
+                    .position(|header| header.trim().eq_ignore_ascii_case(&column.name));
+                (column, index)
+            })
+            .collect();
+        if mapping.iter().all(|(_, index)| index.is_none()) {
+            return Err(format!(
+                "None of the CSV headers of {} match a column of {}",
+                path, table
+            ));
+        }
+
+        Ok(Self {
+            path: path.to_string(),
+            table: table.to_string(),
+            headers: headers,
+            mapping: mapping,
+            rows: records,
+        })
+    }
+
+    /// CSV headers that match no table column and are left out of the import.
+    pub fn unmapped_headers(&self) -> Vec<&String> {
+        return self
+            .headers
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !self.mapping.iter().any(|(_, i)| *i == Some(*index)))
+            .map(|(_, header)| header)
+            .collect();
+    }
+
+    /// The values of `row` for the mapped columns, checked against each column's type.
+    /// Empty fields are NULL.
+    pub fn row_values(&self, row: &[String]) -> Result<Vec<ColumnValue>, String> {
+        if row.len() != self.headers.len() {
+            return Err(format!(
+                "has {} fields but the header has {}",
+                row.len(),
+                self.headers.len()
+            ));
+        }
+        let mut values = vec![];
+        for (column, index) in self.mapping.iter() {
+            let field = match index {
+                Some(index) => &row[*index],
+                None => continue,
+            };
+            let value = if field.is_empty() {
+                if !column.nullable {
+                    return Err(format!(
+                        "{}: empty value for a NOT NULL column",
+                        column.name
+                    ));
+                }
+                CellValue::Null
+            } else {
+                check_type(&column.column_type, field)
+                    .map_err(|message| format!("{}: {}", column.name, message))?
+            };
+            values.push(ColumnValue {
+                column: column.name.clone(),
+                column_type: column.column_type.clone(),
+                value: value,
+            });
+        }
+        return Ok(values);
+    }
+
+    pub fn type_errors(&self) -> Vec<RowError> {
+        return self
+            .rows
+            .iter()
+            .enumerate()
+            .filter_map(|(index, row)| {
+                self.row_values(row).err().map(|message| RowError {
+                    row: index + 1,
+                    message: message,
+                })
+            })
+            .collect();
+    }
+}
+
+/// How a CSV import ended.
+pub enum ImportOutcome {
+    /// Every row went in, or the user kept the rows that did, and the transaction was
+    /// committed; with the number of rows imported.
+    Committed(usize),
+    /// Some rows failed, so the transaction is left open for the user to commit the
+    /// other rows or roll back.
+    Failed {
+        plan: ImportPlan,
+        imported: usize,
+        errors: Vec<RowError>,
+    },
+    RolledBack,
+}
+
+/// Inserts the plan's rows batch by batch inside one transaction, calling `progress` with
+/// the rows done so far and the total after each batch. Commits right away if every row
+/// went in, otherwise leaves the transaction open. On error everything is rolled back.
+pub async fn run_import(
+    sql_client: &dyn SqlClient,
+    plan: ImportPlan,
+    progress: impl Fn(usize, usize),
+) -> Result<ImportOutcome, sqlx::Error> {
+    let total = plan.rows.len();
+    sql_client.begin_transaction().await?;
+
+    let mut imported = 0;
+    let mut errors = vec![];
+    for start in (0..total).step_by(IMPORT_BATCH_SIZE) {
+        match import_batch(sql_client, &plan, start).await {
+            Ok((batch_imported, batch_errors)) => {
+                imported += batch_imported;
+                errors.extend(batch_errors);
+            }
+            Err(e) => {
+                // The savepoint itself failed, so nothing reliable is left; undo everything.
+                sql_client.rollback_transaction().await.ok();
+                return Err(e);
+            }
+        }
+        progress((start + IMPORT_BATCH_SIZE).min(total), total);
+    }
+
+    if !errors.is_empty() {
+        return Ok(ImportOutcome::Failed {
+            plan: plan,
+            imported: imported,
+            errors: errors,
+        });
+    }
+    sql_client.commit_transaction().await?;
+    return Ok(ImportOutcome::Committed(imported));
+}
+
+/// Inserts the batch of rows starting at `start` inside the open transaction.
+/// The batch goes in as one multi-row INSERT; if that fails it is retried row by row
+/// so each failing row is reported. Returns the number of rows inserted.
+async fn import_batch(
+    sql_client: &dyn SqlClient,
+    plan: &ImportPlan,
+    start: usize,
+) -> Result<(usize, Vec<RowError>), sqlx::Error> {
+    let end = (start + IMPORT_BATCH_SIZE).min(plan.rows.len());
+    let mut errors = vec![];
+    let mut batch = vec![];
+    for (index, row) in plan.rows[start..end].iter().enumerate() {
+        match plan.row_values(row) {
+            Ok(values) => batch.push((start + index + 1, values)),
+            Err(message) => errors.push(RowError {
+                row: start + index + 1,
+                message: message,
+            }),
+        }
+    }
+    if batch.is_empty() {
+        return Ok((0, errors));
+    }
+
+    let rows: Vec<Vec<ColumnValue>> = batch.iter().map(|(_, values)| values.clone()).collect();
+    if try_insert(sql_client, &plan.table, &rows).await?.is_ok() {
+        return Ok((batch.len(), errors));
+    }
+
+    let mut imported = 0;
+    for (row, values) in batch.into_iter() {
+        match try_insert(sql_client, &plan.table, &[values]).await? {
+            Ok(()) => imported += 1,
+            Err(e) => errors.push(RowError {
+                row: row,
+                message: e.to_string(),
+            }),
+        }
+    }
+    errors.sort_by_key(|error| error.row);
+    return Ok((imported, errors));
+}
+
+/// Runs one INSERT behind a savepoint. The outer error means the savepoint itself failed;
+/// the inner one is the INSERT's error after it was undone.
+async fn try_insert(
+    sql_client: &dyn SqlClient,
+    table: &str,
+    rows: &[Vec<ColumnValue>],
+) -> Result<Result<(), sqlx::Error>, sqlx::Error> {
+    let statement = Statement::insert_rows(sql_client, table, rows);
+    sql_client
+        .execute_in_transaction(&Statement::raw(&format!("SAVEPOINT {}", SAVEPOINT)))
+        .await?;
+    let res = sql_client.execute_in_transaction(&statement).await;
+    if res.is_err() {
+        sql_client
+            .execute_in_transaction(&Statement::raw(&format!(
+                "ROLLBACK TO SAVEPOINT {}",
+                SAVEPOINT
+            )))
+            .await?;
+    }
+    sql_client
+        .execute_in_transaction(&Statement::raw(&format!("RELEASE SAVEPOINT {}", SAVEPOINT)))
+        .await?;
+    return Ok(res.map(|_| ()));
+}
+
+/// Checks `field` against a declared column type such as `int(11)` or `TIMESTAMP`
+/// and converts numbers and booleans so they bind with their own type.
+/// Types not listed here, e.g. `interval` or `point`, are left for the server to check.
+fn check_type(column_type: &str, field: &str) -> Result<CellValue, String> {
+    let type_name = base_type(column_type);
+    let field = field.trim();
+    if BOOLEAN_TYPES.contains(&type_name.as_str()) || column_type.eq_ignore_ascii_case("tinyint(1)")
+    {
+        return match field.to_lowercase().as_str() {
+            "1" | "true" | "t" | "yes" => Ok(CellValue::Bool(true)),
+            "0" | "false" | "f" | "no" => Ok(CellValue::Bool(false)),
+            _ => Err(format!("'{}' is not a boolean", field)),
+        };
+    }
+    if INTEGER_TYPES.contains(&type_name.as_str()) {
+        if let Ok(value) = field.parse::<i64>() {
+            return Ok(CellValue::Int(value));
+        }
+        return field
+            .parse::<u64>()
+            .map(CellValue::UInt)
+            .map_err(|_| format!("'{}' is not an integer", field));
+    }
+    if FLOAT_TYPES.contains(&type_name.as_str()) {
+        return field
+            .parse::<f64>()
+            .map(CellValue::Float)
+            .map_err(|_| format!("'{}' is not a number", field));
+    }
+    if DECIMAL_TYPES.contains(&type_name.as_str()) {
+        // Kept as text so no precision is lost on the way to the server.
+        return match field.parse::<f64>() {
+            Ok(_) => Ok(CellValue::Text(field.to_string())),
+            Err(_) => Err(format!("'{}' is not a number", field)),
+        };
+    }
+    if DATETIME_TYPES.contains(&type_name.as_str()) {
+        let is_datetime = ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"]
+            .iter()
+            .any(|format| NaiveDateTime::parse_from_str(field, format).is_ok())
+            || DateTime::parse_from_rfc3339(field).is_ok();
+        if !is_datetime {
+            return Err(format!("'{}' is not a date and time", field));
+        }
+    } else if type_name == "date" {
+        if NaiveDate::parse_from_str(field, "%Y-%m-%d").is_err() {
+            return Err(format!("'{}' is not a date (YYYY-MM-DD)", field));
+        }
+    } else if TIME_TYPES.contains(&type_name.as_str())
+        && NaiveTime::parse_from_str(field, "%H:%M:%S%.f").is_err()
+    {
+        return Err(format!("'{}' is not a time (HH:MM:SS)", field));
+    }
+    return Ok(CellValue::Text(field.to_string()));
+}
+
+/// Splits RFC 4180 CSV into records of fields. Blank lines are skipped.
+fn parse_csv(content: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut records = vec![];
+    let mut record = vec![];
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                c => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' => in_quotes = true,
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            c => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(String::from("CSV ends inside a quoted field"));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records.retain(|record| !(record.len() == 1 && record[0].is_empty()));
+    return Ok(records);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_type, parse_csv};
+    use crate::model::cell::CellValue;
+
+    #[test]
+    fn parses_quoted_fields() {
+        let records = parse_csv("a,b\r\n\"x, \"\"y\"\"\",\"line\nbreak\"\r\n\n1,\n").unwrap();
+        assert_eq!(
+            records,
+            vec![
+                vec!["a", "b"],
+                vec!["x, \"y\"", "line\nbreak"],
+                vec!["1", ""],
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_a_last_line_without_newline() {
+        assert_eq!(parse_csv("a\n1").unwrap(), vec![vec!["a"], vec!["1"]]);
+        assert_eq!(parse_csv("").unwrap(), Vec::<Vec<String>>::new());
+    }
+
+    #[test]
+    fn refuses_an_unterminated_quote() {
+        assert!(parse_csv("a\n\"1").is_err());
+    }
+
+    #[test]
+    fn converts_integers_by_base_type() {
+        assert!(matches!(
+            check_type("INT(11) UNSIGNED", "7"),
+            Ok(CellValue::Int(7))
+        ));
+        assert!(matches!(
+            check_type("bigint", " -3 "),
+            Ok(CellValue::Int(-3))
+        ));
+        assert!(check_type("integer", "1.5").is_err());
+    }
+
+    #[test]
+    fn leaves_types_that_only_look_like_integers_alone() {
+        assert!(matches!(
+            check_type("interval", "1 day"),
+            Ok(CellValue::Text(_))
+        ));
+        assert!(matches!(
+            check_type("point", "(1,2)"),
+            Ok(CellValue::Text(_))
+        ));
+    }
+
+    #[test]
+    fn checks_other_types() {
+        assert!(matches!(
+            check_type("tinyint(1)", "yes"),
+            Ok(CellValue::Bool(true))
+        ));
+        assert!(matches!(
+            check_type("boolean", "f"),
+            Ok(CellValue::Bool(false))
+        ));
+        assert!(matches!(
+            check_type("double precision", "2.5"),
+            Ok(CellValue::Float(_))
+        ));
+        assert!(check_type("numeric(10,2)", "abc").is_err());
+        assert!(check_type("timestamp(3) without time zone", "2024-01-02 03:04:05").is_ok());
+        assert!(check_type("datetime", "2024-01-02").is_err());
+        assert!(check_type("date", "2024-13-02").is_err());
+        assert!(check_type("time", "10:20:30").is_ok());
+    }
+}
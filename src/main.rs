@@ -1,27 +1,70 @@
-use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+#![allow(
+    clippy::needless_return,
+    clippy::redundant_field_names,
+    clippy::module_inception
+)]
+
+use cli::{prompt_password, CliArgs, USAGE};
+use config::{Config, ConnectionProfile};
+use crossterm::event::{Event, KeyCode, KeyModifiers};
+use db::{
+    lexer::{referenced_tables, split_statements},
+    sql_client::{self, QueryResult, SqlClient, StatementResult},
 };
-use db::sql_client::MySqlClient;
 use dotenv::dotenv;
-use model::{database::DatabaseModel, table::TableModel};
+use events::{AppEvent, ConnectionState, Events, LoadResult, Loader, TableLoad};
+use export::ExportFormat;
+use history::HistoryEntry;
+use import::{ImportOutcome, ImportPlan};
+use model::{
+    cell::CellValue,
+    database::DatabaseModel,
+    table::{RecordSort, TableColumnModel, TableModel, TableRecordModel},
+};
 
-use std::{error::Error, io};
+use std::{
+    env,
+    error::Error,
+    fs,
+    io::{self, Read, Write},
+    mem,
+    os::unix::fs::OpenOptionsExt,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use terminal::{resume_terminal, suspend_terminal, TerminalGuard};
 use tui::{
-    backend::{Backend, CrosstermBackend},
+    backend::Backend,
+    layout::{Constraint, Direction, Layout},
     Frame, Terminal,
 };
 use ui::{
-    layouts::{change_db::ChangeDBLayout, edit_sql::EditSQLLayout, normal::NormalLayout},
-    widgets::{ctx::WidgetCtx, tab::TableMode},
+    layouts::{
+        change_db::ChangeDBLayout, edit_sql::EditSQLLayout, export::ExportLayout,
+        file_prompt::FilePromptLayout, history_search::HistorySearchLayout, normal::NormalLayout,
+        profile_picker::ProfilePickerLayout,
+    },
+    widgets::{
+        ctx::WidgetCtx, export_prompt::ExportSource, file_prompt::FileAction, import::ImportPhase,
+        profile_picker::PickerPhase, tab::TableMode, table::table_record::TableRecordWdg,
+    },
 };
 
 use crate::ui::layouts::layout_trait::LayoutTrait;
 use crate::ui::layouts::layout_trait::NormalLayoutTrait;
 
+mod batch;
+mod cli;
+mod config;
 mod db;
+mod events;
+mod export;
+mod history;
+mod import;
 mod model;
+mod terminal;
 mod ui;
 mod utils;
 
@@ -29,46 +72,221 @@ enum WidgetMode {
     Normal,
     ChangeDB,
     EditSQL,
+    EditFilter,
+    EditCell,
+    InsertRow,
+    ReviewChanges,
+    Export,
+    Import,
+    HistorySearch,
+    FilePrompt,
+    PickProfile,
 }
 
 pub struct App {
     widget_mode: WidgetMode,
+    /// Longest a statement from the SQL editor may run, set in seconds by `STATEMENT_TIMEOUT`.
+    statement_timeout: Option<Duration>,
+    loader: Loader,
 }
 
 impl App {
     fn new() -> Self {
         Self {
             widget_mode: WidgetMode::Normal,
+            statement_timeout: env::var("STATEMENT_TIMEOUT")
+                .ok()
+                .and_then(|secs| secs.parse().ok())
+                .map(Duration::from_secs),
+            loader: Loader::new(),
         }
     }
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    enable_raw_mode().unwrap();
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture).unwrap();
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
-
     // Set config
     dotenv().ok();
 
-    let mut mysql_client = MySqlClient::new().await;
-    let database_model = DatabaseModel::new(&mut mysql_client).await;
+    let args = CliArgs::parse(env::args().skip(1))?;
+    if args.help {
+        print!("{}", USAGE);
+        return Ok(());
+    }
+    let password = match args.prompt_password {
+        true => Some(prompt_password("Enter password: ")?),
+        false => None,
+    };
+    let base_db_url = args.connection_url(password.as_deref())?;
+
+    // SQL given with -e or piped in is run without the screen
+    let stdin_is_terminal = unsafe { libc::isatty(libc::STDIN_FILENO) } == 1;
+    if args.execute.is_some() || !stdin_is_terminal {
+        let sql = match args.execute.clone() {
+            Some(sql) => sql,
+            None => {
+                let mut sql = String::new();
+                io::stdin().read_to_string(&mut sql)?;
+                sql
+            }
+        };
+        let base_db_url = base_db_url.ok_or(
+            "Pass a database URL as the first argument or set DATABASE_URL to run SQL with -e or from stdin",
+        )?;
+        let sql_client = sql_client::connect(base_db_url, false).await?;
+        if let Some(database) = args
+            .database
+            .clone()
+            .or_else(|| sql_client.default_database())
+        {
+            sql_client.reconnect(database).await?;
+        }
+        if !batch::run_batch(sql_client.as_ref(), &sql, args.format).await {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let config = Config::load()?;
+
+    // Connect before taking over the terminal, so connection errors print normally
+    let connection = match base_db_url {
+        Some(base_db_url) => {
+            let sql_client = sql_client::connect(base_db_url, false).await?;
+            let database_model =
+                DatabaseModel::new(sql_client.as_ref(), args.database.clone()).await?;
+            Some((sql_client, database_model))
+        }
+        None if config.connections.is_empty() => {
+            let config_path = config
+                .path
+                .map(|path| path.display().to_string())
+                .unwrap_or_else(|| String::from("the config file"));
+            return Err(format!(
+                "Pass a database URL as the first argument, set DATABASE_URL or add connections to {}",
+                config_path
+            )
+            .into());
+        }
+        None => None,
+    };
+
+    // Restores the terminal when dropped at the end of `main`, and on panic
+    let (_terminal_guard, mut terminal) = TerminalGuard::new()?;
 
     let mut app = App::new();
-    let mut widget_ctx = WidgetCtx::new(database_model);
+    let mut events = Events::new();
+    // Without a URL the first connection is picked from the config file, over an empty screen
+    let mut widget_ctx = WidgetCtx::new(DatabaseModel::default(), config.connections.clone(), None);
+    let (mut sql_client, database_model, active_profile) = match connection {
+        Some((sql_client, database_model)) => (sql_client, database_model, None),
+        None => match pick_profile(&mut terminal, &mut app, &mut events, &mut widget_ctx).await? {
+            Some((index, sql_client, database_model)) => (sql_client, database_model, Some(index)),
+            None => return Ok(()),
+        },
+    };
+    widget_ctx = WidgetCtx::new(database_model, config.connections.clone(), active_profile);
+    if let Some(table) = args.table.as_ref() {
+        if !widget_ctx.table_list.select_table(table) {
+            widget_ctx.status_bar.set_error_msg(format!(
+                "Database {} has no table {}",
+                widget_ctx.database.current_database, table
+            ));
+        }
+    }
+    start_session(&mut app, &mut events, &sql_client, &mut widget_ctx);
 
     loop {
         terminal.draw(|f| render_layout(f, &mut app, &mut widget_ctx))?;
 
-        if let Event::Key(key) = event::read()? {
+        let event = match events.next().await {
+            AppEvent::Input(event) => event,
+            AppEvent::Tick => {
+                widget_ctx.table.tick();
+                continue;
+            }
+            AppEvent::Loaded { load_id, result } => {
+                if app.loader.finish(load_id) {
+                    finish_load(&mut app, &events, &sql_client, result, &mut widget_ctx);
+                }
+                continue;
+            }
+            AppEvent::Progress {
+                load_id,
+                action,
+                done,
+                total,
+            } => {
+                if app.loader.is_current(load_id) {
+                    widget_ctx.sql_output.set_progress_msg(action, done, total);
+                }
+                continue;
+            }
+            AppEvent::Columns {
+                database,
+                table,
+                columns,
+            } => {
+                if database == widget_ctx.database.current_database {
+                    widget_ctx.completion.column_cache.insert(table, columns);
+                    if let WidgetMode::EditSQL = app.widget_mode {
+                        update_completion(&events, &sql_client, &mut widget_ctx);
+                    }
+                }
+                continue;
+            }
+            AppEvent::Connection(ConnectionState::Lost {
+                attempt,
+                error,
+                retry_in,
+            }) => {
+                widget_ctx
+                    .status_bar
+                    .set_connection_lost_msg(attempt, &error, retry_in);
+                continue;
+            }
+            AppEvent::Terminate => break,
+            AppEvent::Suspend => {
+                suspend_process(&mut terminal, &mut events).await?;
+                continue;
+            }
+            AppEvent::Connection(ConnectionState::Restored) => {
+                widget_ctx.status_bar.set_reconnected_msg();
+                // What is on screen may be stale or failed to load meanwhile
+                if !widget_ctx.table.is_loading() && widget_ctx.change_set.change_set.is_empty() {
+                    let table = widget_ctx.table.record_widget.current_table.clone();
+                    start_table_load(&mut app, &events, &sql_client, table, &mut widget_ctx);
+                }
+                continue;
+            }
+        };
+        if let Event::Key(key) = event {
+            widget_ctx.status_bar.dismiss();
+            // Raw mode turns off the terminal's own Ctrl-Z handling
+            if key.code == KeyCode::Char('z') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                suspend_process(&mut terminal, &mut events).await?;
+                continue;
+            }
             match app.widget_mode {
+                // The grids are hidden while a table loads, so only keys that leave them work;
+                // changes being written may only be left by quitting
+                WidgetMode::Normal
+                    if widget_ctx.table.is_loading()
+                        && key.code != KeyCode::Char('q')
+                        && (widget_ctx.table.is_writing()
+                            || !(matches!(key.code, KeyCode::Char('a' | 'b' | 'c' | 'e'))
+                                || (key.code == KeyCode::Enter
+                                    && widget_ctx.table.can_cancel_loading()))) =>
+                {
+                    if key.code == KeyCode::Esc && widget_ctx.table.can_cancel_loading() {
+                        app.loader.cancel();
+                        widget_ctx.table.finish_loading();
+                        let current_table = widget_ctx.table.record_widget.current_table.clone();
+                        widget_ctx.filter_input.load(&current_table);
+                    }
+                }
                 WidgetMode::Normal => match key.code {
                     KeyCode::Char('q') => {
-                        disable_raw_mode()?;
-                        terminal.show_cursor()?;
                         break;
                     }
                     KeyCode::Char('a') => {
@@ -81,7 +299,13 @@ async fn main() -> Result<(), Box<dyn Error>> {
                         app.widget_mode = WidgetMode::EditSQL;
                     }
                     KeyCode::Char('c') => {
-                        app.widget_mode = WidgetMode::ChangeDB;
+                        if widget_ctx.change_set.change_set.is_empty() {
+                            app.widget_mode = WidgetMode::ChangeDB;
+                        } else {
+                            widget_ctx
+                                .sql_output
+                                .set_error_msg(pending_changes_msg(&widget_ctx));
+                        }
                     }
                     KeyCode::Char('0') => {
                         widget_ctx.tab.mode = TableMode::Records;
@@ -89,11 +313,156 @@ async fn main() -> Result<(), Box<dyn Error>> {
                     KeyCode::Char('1') => {
                         widget_ctx.tab.mode = TableMode::Columns;
                     }
+                    KeyCode::Char('s') => {
+                        if let TableMode::Records = widget_ctx.tab.mode {
+                            let record_widget = &widget_ctx.table.record_widget;
+                            if let Some(column) = record_widget.selected_column() {
+                                let record_model = record_widget.table_record_model();
+                                let sort = record_model.next_sort(column.clone());
+                                let filter = record_model.filter.clone();
+                                start_records_reload(
+                                    &mut app,
+                                    &events,
+                                    &sql_client,
+                                    filter,
+                                    sort,
+                                    &mut widget_ctx,
+                                );
+                            }
+                        }
+                    }
+                    KeyCode::Char('f') => {
+                        if let TableMode::Records = widget_ctx.tab.mode {
+                            app.widget_mode = WidgetMode::EditFilter;
+                        }
+                    }
+                    KeyCode::Char('i') => {
+                        if refuse_if_read_only(&mut widget_ctx) {
+                            continue;
+                        }
+                        if let TableMode::Records = widget_ctx.tab.mode {
+                            match widget_ctx.table.selected_cell_edit() {
+                                Ok(mut cell_edit) => {
+                                    if let Some(value) =
+                                        widget_ctx.change_set.change_set.pending_value(
+                                            &cell_edit.primary_key,
+                                            &cell_edit.target.column,
+                                        )
+                                    {
+                                        cell_edit.target.value = value.clone();
+                                    }
+                                    widget_ctx.cell_editor.open(cell_edit);
+                                    app.widget_mode = WidgetMode::EditCell;
+                                }
+                                Err(msg) => widget_ctx.sql_output.set_error_msg(msg),
+                            }
+                        }
+                    }
+                    KeyCode::Char('n') => {
+                        if refuse_if_read_only(&mut widget_ctx) {
+                            continue;
+                        }
+                        if let TableMode::Records = widget_ctx.tab.mode {
+                            widget_ctx.row_form.open(
+                                widget_ctx.table.record_widget.current_table.clone(),
+                                widget_ctx
+                                    .table
+                                    .column_widget
+                                    .table_column_model()
+                                    .column_definitions(),
+                            );
+                            app.widget_mode = WidgetMode::InsertRow;
+                        }
+                    }
+                    KeyCode::Char('d') => {
+                        if refuse_if_read_only(&mut widget_ctx) {
+                            continue;
+                        }
+                        if let TableMode::Records = widget_ctx.tab.mode {
+                            match widget_ctx.table.selected_primary_key() {
+                                Ok(primary_key) => widget_ctx.change_set.change_set.toggle_delete(
+                                    sql_client.as_ref(),
+                                    &widget_ctx.table.record_widget.current_table,
+                                    primary_key,
+                                ),
+                                Err(msg) => widget_ctx.sql_output.set_error_msg(msg),
+                            }
+                        }
+                    }
+                    KeyCode::Char('x') => {
+                        if let TableMode::Records = widget_ctx.tab.mode {
+                            widget_ctx.export_prompt.open(
+                                ExportSource::Table,
+                                &widget_ctx.table.record_widget.current_table,
+                            );
+                            app.widget_mode = WidgetMode::Export;
+                        }
+                    }
+                    KeyCode::Char('l') => {
+                        if refuse_if_read_only(&mut widget_ctx) {
+                            continue;
+                        }
+                        if let TableMode::Records = widget_ctx.tab.mode {
+                            widget_ctx.import.open();
+                            app.widget_mode = WidgetMode::Import;
+                        }
+                    }
+                    KeyCode::Char('p') => {
+                        if refuse_if_read_only(&mut widget_ctx) {
+                            continue;
+                        }
+                        widget_ctx.change_set.open();
+                        app.widget_mode = WidgetMode::ReviewChanges;
+                    }
+                    KeyCode::Char('o') => {
+                        if !widget_ctx.change_set.change_set.is_empty() {
+                            widget_ctx
+                                .sql_output
+                                .set_error_msg(pending_changes_msg(&widget_ctx));
+                            continue;
+                        }
+                        if widget_ctx.profile_picker.profiles.is_empty() {
+                            widget_ctx.sql_output.set_error_msg(String::from(
+                                "There are no connections in the config file to switch to",
+                            ));
+                            continue;
+                        }
+                        if let Some((index, client, database_model)) =
+                            pick_profile(&mut terminal, &mut app, &mut events, &mut widget_ctx)
+                                .await?
+                        {
+                            sql_client = client;
+                            widget_ctx = WidgetCtx::new(
+                                database_model,
+                                config.connections.clone(),
+                                Some(index),
+                            );
+                            start_session(&mut app, &mut events, &sql_client, &mut widget_ctx);
+                        }
+                    }
                     KeyCode::Up => {
                         widget_ctx.table.move_up(&widget_ctx.tab.mode);
                     }
                     KeyCode::Down => {
                         widget_ctx.table.move_down(&widget_ctx.tab.mode);
+                        // With the grid shown, a busy loader is already fetching the next page
+                        if widget_ctx.table.record_widget.is_near_loaded_end()
+                            && !app.loader.is_busy()
+                        {
+                            let record_widget = &widget_ctx.table.record_widget;
+                            let table = record_widget.current_table.clone();
+                            let record_model = record_widget.table_record_model();
+                            let offset = record_model.records.len();
+                            let page =
+                                record_model.fetch_next_page(sql_client.clone(), table.clone());
+                            app.loader.start(&events, async move {
+                                LoadResult::Page {
+                                    table: table,
+                                    offset: offset,
+                                    page: page.await,
+                                }
+                            });
+                        }
                     }
                     KeyCode::Right => {
                         widget_ctx.table.move_right(&widget_ctx.tab.mode);
@@ -102,21 +471,30 @@ async fn main() -> Result<(), Box<dyn Error>> {
                         widget_ctx.table.move_left(&widget_ctx.tab.mode);
                     }
                     KeyCode::Enter => {
+                        if !widget_ctx.change_set.change_set.is_empty() {
+                            widget_ctx
+                                .sql_output
+                                .set_error_msg(pending_changes_msg(&widget_ctx));
+                            continue;
+                        }
                         widget_ctx.table_list.change_table();
-                        if !widget_ctx
+                        let table = widget_ctx.table_list.current_table.to_string();
+                        if widget_ctx
                             .table
                             .record_widget
-                            .is_current_table(widget_ctx.table_list.current_table.to_string())
+                            .is_current_table(table.clone())
                         {
-                            // reset table
-                            let table_model = TableModel::new(
-                                &mysql_client,
-                                widget_ctx.table_list.current_table.to_string(),
-                            )
-                            .await;
-                            widget_ctx.table.reset_table_widget(
-                                widget_ctx.table_list.current_table.to_string(),
-                                table_model,
+                            // Back on the table already shown, drop the one still loading
+                            app.loader.cancel();
+                            widget_ctx.table.finish_loading();
+                            widget_ctx.filter_input.load(&table);
+                        } else {
+                            start_table_load(
+                                &mut app,
+                                &events,
+                                &sql_client,
+                                table,
+                                &mut widget_ctx,
                             );
                         }
                     }
@@ -125,25 +503,23 @@ async fn main() -> Result<(), Box<dyn Error>> {
                 WidgetMode::ChangeDB => match key.code {
                     KeyCode::Enter => {
                         // change database
-                        widget_ctx.database.change_database();
-                        mysql_client
-                            .reconnect(widget_ctx.database.current_database.to_string())
-                            .await;
-
-                        let new_tables = mysql_client
-                            .get_table_list(widget_ctx.database.current_database.to_string())
-                            .await;
-                        let new_table_name = &new_tables[0];
-                        widget_ctx.table_list.change_table();
-                        widget_ctx.table_list.change_tables(new_tables.clone());
-
-                        // reset table
-                        let table_model =
-                            TableModel::new(&mysql_client, new_table_name.to_string()).await;
-                        widget_ctx.table.reset_table_widget(
-                            widget_ctx.table_list.current_table.to_string(),
-                            table_model,
-                        );
+                        if let Some(database) = widget_ctx.database.selected_database() {
+                            widget_ctx.completion.column_cache.clear();
+                            widget_ctx
+                                .table
+                                .start_loading(format!("database {}", database), false);
+                            let client = sql_client.clone();
+                            app.loader.start(&events, async move {
+                                let tables = match client.reconnect(database.clone()).await {
+                                    Ok(()) => client.get_table_list(database.clone()).await,
+                                    Err(e) => Err(e),
+                                };
+                                LoadResult::Database {
+                                    database: database,
+                                    tables: tables,
+                                }
+                            });
+                        }
                         app.widget_mode = WidgetMode::Normal;
                     }
                     KeyCode::Up => {
@@ -157,43 +533,1328 @@ async fn main() -> Result<(), Box<dyn Error>> {
                     }
                     _ => {}
                 },
-                WidgetMode::EditSQL => match key.code {
+                WidgetMode::EditFilter => match key.code {
+                    KeyCode::Enter => {
+                        let filter = widget_ctx.filter_input.applied_input();
+                        let sort = widget_ctx
+                            .table
+                            .record_widget
+                            .table_record_model()
+                            .sort
+                            .clone();
+                        start_records_reload(
+                            &mut app,
+                            &events,
+                            &sql_client,
+                            filter,
+                            sort,
+                            &mut widget_ctx,
+                        );
+                        app.widget_mode = WidgetMode::Normal;
+                    }
+                    KeyCode::Char(c) => {
+                        widget_ctx.filter_input.input.push(c);
+                    }
+                    KeyCode::Backspace => {
+                        widget_ctx.filter_input.input.pop();
+                    }
+                    KeyCode::Esc => {
+                        widget_ctx
+                            .filter_input
+                            .load(&widget_ctx.table.record_widget.current_table);
+                        app.widget_mode = WidgetMode::Normal;
+                    }
+                    _ => {}
+                },
+                WidgetMode::EditCell => match key.code {
+                    KeyCode::Enter => {
+                        if let Some((cell_edit, assignment)) = widget_ctx.cell_editor.take() {
+                            widget_ctx.change_set.change_set.stage_update(
+                                sql_client.as_ref(),
+                                &cell_edit.table,
+                                cell_edit.primary_key,
+                                assignment,
+                            );
+                        }
+                        app.widget_mode = WidgetMode::Normal;
+                    }
+                    KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        widget_ctx.cell_editor.toggle_null();
+                    }
+                    KeyCode::Char(c) => {
+                        widget_ctx.cell_editor.push(c);
+                    }
+                    KeyCode::Backspace => {
+                        widget_ctx.cell_editor.pop();
+                    }
+                    KeyCode::Esc => {
+                        widget_ctx.cell_editor.close();
+                        app.widget_mode = WidgetMode::Normal;
+                    }
+                    _ => {}
+                },
+                WidgetMode::InsertRow => match key.code {
+                    KeyCode::Enter => match widget_ctx.row_form.take_values() {
+                        Ok(values) => {
+                            widget_ctx.change_set.change_set.stage_insert(
+                                sql_client.as_ref(),
+                                &widget_ctx.table.record_widget.current_table,
+                                values,
+                            );
+                            app.widget_mode = WidgetMode::Normal;
+                        }
+                        Err(msg) => widget_ctx.sql_output.set_error_msg(msg),
+                    },
+                    KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        widget_ctx.row_form.toggle_null();
+                    }
+                    KeyCode::Up | KeyCode::BackTab => {
+                        widget_ctx.row_form.move_up();
+                    }
+                    KeyCode::Down | KeyCode::Tab => {
+                        widget_ctx.row_form.move_down();
+                    }
+                    KeyCode::Char(c) => {
+                        widget_ctx.row_form.push(c);
+                    }
+                    KeyCode::Backspace => {
+                        widget_ctx.row_form.pop();
+                    }
+                    KeyCode::Esc => {
+                        widget_ctx.row_form.close();
+                        app.widget_mode = WidgetMode::Normal;
+                    }
+                    _ => {}
+                },
+                WidgetMode::ReviewChanges => match key.code {
+                    KeyCode::Char('c') => {
+                        let statements = widget_ctx.change_set.change_set.statements();
+                        widget_ctx.table.start_writing(format!(
+                            "Committing {} changes",
+                            widget_ctx.change_set.change_set.changes.len()
+                        ));
+                        let client = sql_client.clone();
+                        app.loader.start(&events, async move {
+                            LoadResult::Committed {
+                                rows_affected: client.execute_transaction(&statements).await,
+                            }
+                        });
+                        app.widget_mode = WidgetMode::Normal;
+                    }
+                    KeyCode::Char('r') => {
+                        widget_ctx.change_set.change_set.clear();
+                        app.widget_mode = WidgetMode::Normal;
+                    }
+                    KeyCode::Char('x') => {
+                        widget_ctx.change_set.remove_selected();
+                    }
+                    KeyCode::Up => {
+                        widget_ctx.change_set.move_up();
+                    }
+                    KeyCode::Down => {
+                        widget_ctx.change_set.move_down();
+                    }
+                    KeyCode::Esc => {
+                        app.widget_mode = WidgetMode::Normal;
+                    }
+                    _ => {}
+                },
+                WidgetMode::Export => match key.code {
                     KeyCode::Enter => {
-                        let res = mysql_client
-                            .execute_input_query(widget_ctx.sql_input.input.clone())
-                            .await;
+                        let path = widget_ctx.export_prompt.input.clone();
+                        let format = widget_ctx.export_prompt.format;
+                        let overwrite = widget_ctx.export_prompt.overwrite;
+                        let res = match widget_ctx.export_prompt.source {
+                            ExportSource::Table => {
+                                start_table_export(
+                                    &mut app,
+                                    &events,
+                                    &sql_client,
+                                    path,
+                                    format,
+                                    overwrite,
+                                    &mut widget_ctx,
+                                );
+                                app.widget_mode = export_return_mode(&widget_ctx);
+                                continue;
+                            }
+                            ExportSource::QueryResult => match &widget_ctx.sql_result {
+                                Some(sql_result) => match export::export(
+                                    sql_client.as_ref(),
+                                    sql_result.table_record_model(),
+                                    "result",
+                                    format,
+                                    Path::new(&path),
+                                    overwrite,
+                                ) {
+                                    Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                                        widget_ctx.export_prompt.ask_overwrite();
+                                        continue;
+                                    }
+                                    res => res.map_err(|e| e.to_string()),
+                                },
+                                None => Err(String::from("There is no query result to export")),
+                            },
+                        };
                         match res {
-                            Ok(res) => widget_ctx.sql_output.set_success_msg(res),
-                            Err(e) => widget_ctx.sql_output.set_error_msg(e.to_string()),
+                            Ok(row_count) => widget_ctx.sql_output.set_export_msg(row_count, path),
+                            Err(e) => widget_ctx.sql_output.set_error_msg(e),
                         }
+                        app.widget_mode = export_return_mode(&widget_ctx);
+                    }
+                    KeyCode::Tab => {
+                        widget_ctx.export_prompt.cycle_format();
                     }
                     KeyCode::Char(c) => {
-                        widget_ctx.sql_input.input.push(c);
+                        widget_ctx.export_prompt.push(c);
                     }
                     KeyCode::Backspace => {
-                        widget_ctx.sql_input.input.pop();
+                        widget_ctx.export_prompt.pop();
                     }
                     KeyCode::Esc => {
+                        app.widget_mode = export_return_mode(&widget_ctx);
+                    }
+                    _ => {}
+                },
+                WidgetMode::Import => match (&widget_ctx.import.phase, key.code) {
+                    (ImportPhase::Path, KeyCode::Enter) => {
+                        match ImportPlan::new(
+                            &widget_ctx.import.input,
+                            &widget_ctx.table.record_widget.current_table,
+                            widget_ctx
+                                .table
+                                .column_widget
+                                .table_column_model()
+                                .column_definitions(),
+                        ) {
+                            Ok(plan) => widget_ctx.import.preview(plan),
+                            Err(msg) => widget_ctx.sql_output.set_error_msg(msg),
+                        }
+                    }
+                    (ImportPhase::Path, KeyCode::Char(c)) => {
+                        widget_ctx.import.input.push(c);
+                    }
+                    (ImportPhase::Path, KeyCode::Backspace) => {
+                        widget_ctx.import.input.pop();
+                    }
+                    (ImportPhase::Preview, KeyCode::Enter) => {
+                        if let Some(plan) = widget_ctx.import.plan.take() {
+                            widget_ctx.table.start_writing(format!(
+                                "Importing {} rows into {}",
+                                plan.rows.len(),
+                                plan.table
+                            ));
+                            let client = sql_client.clone();
+                            app.loader.start_with_progress(
+                                &events,
+                                "Importing",
+                                move |progress| async move {
+                                    LoadResult::Import(
+                                        import::run_import(client.as_ref(), plan, |done, total| {
+                                            progress.report(done, total)
+                                        })
+                                        .await,
+                                    )
+                                },
+                            );
+                        }
+                        app.widget_mode = WidgetMode::Normal;
+                    }
+                    (ImportPhase::Report, KeyCode::Char('c')) => {
+                        let imported = widget_ctx.import.imported;
+                        widget_ctx
+                            .table
+                            .start_writing(format!("Committing {} imported rows", imported));
+                        let client = sql_client.clone();
+                        app.loader.start(&events, async move {
+                            LoadResult::Import(
+                                client
+                                    .commit_transaction()
+                                    .await
+                                    .map(|()| ImportOutcome::Committed(imported)),
+                            )
+                        });
+                        app.widget_mode = WidgetMode::Normal;
+                    }
+                    (ImportPhase::Report, KeyCode::Char('r')) => {
+                        widget_ctx
+                            .table
+                            .start_writing(String::from("Rolling back the import"));
+                        let client = sql_client.clone();
+                        app.loader.start(&events, async move {
+                            LoadResult::Import(
+                                client
+                                    .rollback_transaction()
+                                    .await
+                                    .map(|()| ImportOutcome::RolledBack),
+                            )
+                        });
+                        app.widget_mode = WidgetMode::Normal;
+                    }
+                    (_, KeyCode::Up) => {
+                        widget_ctx.import.scroll_up();
+                    }
+                    (_, KeyCode::Down) => {
+                        widget_ctx.import.scroll_down();
+                    }
+                    (ImportPhase::Path, KeyCode::Esc) | (ImportPhase::Preview, KeyCode::Esc) => {
                         app.widget_mode = WidgetMode::Normal;
                     }
                     _ => {}
                 },
+                WidgetMode::EditSQL
+                    if widget_ctx.completion.is_visible()
+                        && matches!(
+                            key.code,
+                            KeyCode::Tab | KeyCode::Up | KeyCode::Down | KeyCode::Esc
+                        ) =>
+                {
+                    match key.code {
+                        KeyCode::Tab => {
+                            if let Some(word) = widget_ctx.completion.selected() {
+                                widget_ctx.sql_input.buffer.complete_word(&word);
+                            }
+                            widget_ctx.completion.close();
+                        }
+                        KeyCode::Up => {
+                            widget_ctx.completion.move_up();
+                        }
+                        KeyCode::Down => {
+                            widget_ctx.completion.move_down();
+                        }
+                        _ => {
+                            widget_ctx.completion.close();
+                        }
+                    }
+                }
+                WidgetMode::EditSQL => {
+                    widget_ctx.completion.close();
+                    match key.code {
+                        KeyCode::F(5) => {
+                            execute_sql_input(
+                                &mut terminal,
+                                &mut app,
+                                &mut events,
+                                sql_client.as_ref(),
+                                &mut widget_ctx,
+                            )
+                            .await?;
+                        }
+                        KeyCode::Enter
+                            if key
+                                .modifiers
+                                .intersects(KeyModifiers::ALT | KeyModifiers::CONTROL) =>
+                        {
+                            execute_sql_input(
+                                &mut terminal,
+                                &mut app,
+                                &mut events,
+                                sql_client.as_ref(),
+                                &mut widget_ctx,
+                            )
+                            .await?;
+                        }
+                        KeyCode::Enter => {
+                            widget_ctx.sql_input.buffer.insert_newline();
+                        }
+                        KeyCode::Up if key.modifiers.contains(KeyModifiers::ALT) => {
+                            if let Some(sql_result) = widget_ctx.sql_result.as_mut() {
+                                sql_result.move_up();
+                            }
+                        }
+                        KeyCode::Down if key.modifiers.contains(KeyModifiers::ALT) => {
+                            if let Some(sql_result) = widget_ctx.sql_result.as_mut() {
+                                sql_result.move_down();
+                            }
+                        }
+                        KeyCode::Right if key.modifiers.contains(KeyModifiers::ALT) => {
+                            if let Some(sql_result) = widget_ctx.sql_result.as_mut() {
+                                sql_result.move_right();
+                            }
+                        }
+                        KeyCode::Left if key.modifiers.contains(KeyModifiers::ALT) => {
+                            if let Some(sql_result) = widget_ctx.sql_result.as_mut() {
+                                sql_result.move_left();
+                            }
+                        }
+                        KeyCode::Left if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            widget_ctx.sql_input.buffer.move_word_left();
+                        }
+                        KeyCode::Right if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            widget_ctx.sql_input.buffer.move_word_right();
+                        }
+                        KeyCode::Up if widget_ctx.sql_input.buffer.is_on_first_line() => {
+                            let current = widget_ctx.sql_input.input();
+                            if let Some(sql) = widget_ctx.history_search.history.previous(&current)
+                            {
+                                widget_ctx.sql_input.buffer.set_text(sql);
+                            }
+                        }
+                        KeyCode::Down if widget_ctx.sql_input.buffer.is_on_last_line() => {
+                            let current = widget_ctx.sql_input.input();
+                            if let Some(sql) = widget_ctx.history_search.history.next(&current) {
+                                widget_ctx.sql_input.buffer.set_text(sql);
+                            }
+                        }
+                        KeyCode::Up => {
+                            widget_ctx.sql_input.buffer.move_up();
+                        }
+                        KeyCode::Down => {
+                            widget_ctx.sql_input.buffer.move_down();
+                        }
+                        KeyCode::Left => {
+                            widget_ctx.sql_input.buffer.move_left();
+                        }
+                        KeyCode::Right => {
+                            widget_ctx.sql_input.buffer.move_right();
+                        }
+                        KeyCode::Home => {
+                            widget_ctx.sql_input.buffer.move_home();
+                        }
+                        KeyCode::End => {
+                            widget_ctx.sql_input.buffer.move_end();
+                        }
+                        KeyCode::Delete => {
+                            widget_ctx.sql_input.buffer.delete();
+                        }
+                        KeyCode::Char('x') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            if widget_ctx.sql_result.is_some() {
+                                widget_ctx
+                                    .export_prompt
+                                    .open(ExportSource::QueryResult, "result");
+                                app.widget_mode = WidgetMode::Export;
+                            } else {
+                                widget_ctx.sql_output.set_error_msg(String::from(
+                                    "There is no query result to export",
+                                ));
+                            }
+                        }
+                        KeyCode::Char('o') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            widget_ctx.file_prompt.open(
+                                FileAction::Open,
+                                widget_ctx.sql_input.path.as_ref(),
+                                widget_ctx.sql_input.has_unsaved_text(),
+                            );
+                            app.widget_mode = WidgetMode::FilePrompt;
+                        }
+                        KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            match widget_ctx.sql_input.path.clone() {
+                                Some(path) => save_sql_file(&mut widget_ctx, &path),
+                                None => {
+                                    widget_ctx.file_prompt.open(FileAction::Save, None, false);
+                                    app.widget_mode = WidgetMode::FilePrompt;
+                                }
+                            }
+                        }
+                        KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            edit_in_external_editor(&mut terminal, &mut events, &mut widget_ctx)
+                                .await?;
+                        }
+                        KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            widget_ctx.sql_input.continue_on_error =
+                                !widget_ctx.sql_input.continue_on_error;
+                        }
+                        KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            widget_ctx.history_search.open();
+                            app.widget_mode = WidgetMode::HistorySearch;
+                        }
+                        KeyCode::Char(c) => {
+                            widget_ctx.sql_input.buffer.insert_char(c);
+                            update_completion(&events, &sql_client, &mut widget_ctx);
+                        }
+                        KeyCode::Backspace => {
+                            widget_ctx.sql_input.buffer.backspace();
+                            update_completion(&events, &sql_client, &mut widget_ctx);
+                        }
+                        KeyCode::Esc => {
+                            app.widget_mode = WidgetMode::Normal;
+                        }
+                        _ => {}
+                    }
+                }
+                WidgetMode::FilePrompt => match key.code {
+                    KeyCode::Enter => {
+                        let path = widget_ctx.file_prompt.input.trim().to_string();
+                        match widget_ctx.file_prompt.action {
+                            FileAction::Open => match widget_ctx.sql_input.open_file(&path) {
+                                Ok(()) => widget_ctx.sql_output.set_file_msg("open", &path),
+                                Err(e) => widget_ctx
+                                    .sql_output
+                                    .set_error_msg(format!("{}: {}", path, e)),
+                            },
+                            FileAction::Save => save_sql_file(&mut widget_ctx, &path),
+                        }
+                        app.widget_mode = WidgetMode::EditSQL;
+                    }
+                    KeyCode::Char(c) => {
+                        widget_ctx.file_prompt.input.push(c);
+                    }
+                    KeyCode::Backspace => {
+                        widget_ctx.file_prompt.input.pop();
+                    }
+                    KeyCode::Esc => {
+                        app.widget_mode = WidgetMode::EditSQL;
+                    }
+                    _ => {}
+                },
+                WidgetMode::HistorySearch => match key.code {
+                    KeyCode::Enter => {
+                        if let Some(sql) = widget_ctx.history_search.selected_sql() {
+                            widget_ctx.sql_input.buffer.set_text(&sql);
+                        }
+                        app.widget_mode = WidgetMode::EditSQL;
+                    }
+                    KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        widget_ctx.history_search.move_down();
+                    }
+                    KeyCode::Down => {
+                        widget_ctx.history_search.move_down();
+                    }
+                    KeyCode::Up => {
+                        widget_ctx.history_search.move_up();
+                    }
+                    KeyCode::Char(c) => {
+                        widget_ctx.history_search.push(c);
+                    }
+                    KeyCode::Backspace => {
+                        widget_ctx.history_search.pop();
+                    }
+                    KeyCode::Esc => {
+                        app.widget_mode = WidgetMode::EditSQL;
+                    }
+                    _ => {}
+                },
+                // `pick_profile` reads the keys itself while the picker is open
+                WidgetMode::PickProfile => {}
             }
         }
     }
 
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-
     Ok(())
 }
 
+/// Shows why a screen that changes rows stays closed if the connection is read-only.
+fn refuse_if_read_only(widget_ctx: &mut WidgetCtx) -> bool {
+    if !widget_ctx.database.is_read_only() {
+        return false;
+    }
+    widget_ctx.sql_output.set_error_msg(String::from(
+        "This connection is read-only, so its rows cannot be changed",
+    ));
+    return true;
+}
+
+/// Starts using a fresh connection: watches it and loads the first table of its database.
+fn start_session(
+    app: &mut App,
+    events: &mut Events,
+    sql_client: &Arc<dyn SqlClient>,
+    widget_ctx: &mut WidgetCtx<'_>,
+) {
+    events.watch_connection(sql_client.clone());
+    widget_ctx.sql_input.dialect = sql_client.dialect();
+    widget_ctx.history_search.dialect = sql_client.dialect();
+    if widget_ctx.table_list.tables.is_empty() {
+        widget_ctx.sql_output.set_error_msg(format!(
+            "Database {} has no tables",
+            widget_ctx.database.current_database
+        ));
+        return;
+    }
+    let first_table = widget_ctx.table_list.current_table.clone();
+    start_table_load(app, events, sql_client, first_table, widget_ctx);
+}
+
+/// Shows the connection picker until one of the config file's profiles is connected to,
+/// asking for its password first if it prompts for one. Returns the profile's index with
+/// the connection, or None if the picker was closed instead.
+async fn pick_profile<B: Backend + io::Write>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    events: &mut Events,
+    widget_ctx: &mut WidgetCtx<'_>,
+) -> Result<Option<(usize, Arc<dyn SqlClient>, DatabaseModel)>, Box<dyn Error>> {
+    let previous_mode = mem::replace(&mut app.widget_mode, WidgetMode::PickProfile);
+    widget_ctx.profile_picker.open();
+    loop {
+        terminal.draw(|f| render_layout(f, app, widget_ctx))?;
+
+        let key = match events.receive().await {
+            AppEvent::Input(Event::Key(key)) => key,
+            AppEvent::Input(_) => continue,
+            AppEvent::Tick => {
+                widget_ctx.profile_picker.tick();
+                continue;
+            }
+            AppEvent::Loaded {
+                load_id,
+                result: LoadResult::Profile { index, connection },
+            } if app.loader.finish(load_id) => {
+                match connection {
+                    Ok((sql_client, database_model)) => {
+                        app.widget_mode = WidgetMode::Normal;
+                        return Ok(Some((index, sql_client, database_model)));
+                    }
+                    Err(e) => widget_ctx.profile_picker.set_error(format!(
+                        "Could not connect to {}: {}",
+                        widget_ctx.profile_picker.profiles[index].name, e
+                    )),
+                }
+                continue;
+            }
+            // Left for the main loop, which tells if they are stale, e.g. a page still loading
+            // for the connection the picker was opened from
+            event @ (AppEvent::Loaded { .. }
+            | AppEvent::Progress { .. }
+            | AppEvent::Columns { .. }
+            | AppEvent::Connection(_)) => {
+                events.defer(event);
+                continue;
+            }
+            AppEvent::Terminate => {
+                events.defer(AppEvent::Terminate);
+                app.loader.cancel();
+                app.widget_mode = previous_mode;
+                return Ok(None);
+            }
+            AppEvent::Suspend => {
+                suspend_process(terminal, events).await?;
+                continue;
+            }
+        };
+        if key.code == KeyCode::Char('z') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            suspend_process(terminal, events).await?;
+            continue;
+        }
+        match (widget_ctx.profile_picker.phase, key.code) {
+            (PickerPhase::List, KeyCode::Enter) => {
+                let index = match widget_ctx.profile_picker.selected() {
+                    Some(index) => index,
+                    None => continue,
+                };
+                let profile = &widget_ctx.profile_picker.profiles[index];
+                if profile.password_prompt {
+                    widget_ctx.profile_picker.ask_password();
+                    continue;
+                }
+                match profile.password() {
+                    Ok(password) => start_profile_connect(app, events, widget_ctx, index, password),
+                    Err(e) => widget_ctx.profile_picker.set_error(format!("{:#}", e)),
+                }
+            }
+            (PickerPhase::List, KeyCode::Up) => {
+                widget_ctx.profile_picker.move_up();
+            }
+            (PickerPhase::List, KeyCode::Down) => {
+                widget_ctx.profile_picker.move_down();
+            }
+            (PickerPhase::List, KeyCode::Esc | KeyCode::Char('q')) => {
+                app.widget_mode = previous_mode;
+                return Ok(None);
+            }
+            (PickerPhase::Password, KeyCode::Enter) => {
+                if let Some(index) = widget_ctx.profile_picker.selected() {
+                    let password = widget_ctx.profile_picker.take_password();
+                    start_profile_connect(app, events, widget_ctx, index, Some(password));
+                }
+            }
+            (PickerPhase::Password, KeyCode::Char(c)) => {
+                widget_ctx.profile_picker.push(c);
+            }
+            (PickerPhase::Password, KeyCode::Backspace) => {
+                widget_ctx.profile_picker.pop();
+            }
+            (PickerPhase::Password, KeyCode::Esc) => {
+                widget_ctx.profile_picker.open();
+            }
+            (PickerPhase::Connecting, KeyCode::Esc) => {
+                app.loader.cancel();
+                widget_ctx.profile_picker.cancel_connecting();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Connects to the `index`th profile in the background, for `pick_profile` to pick up.
+fn start_profile_connect(
+    app: &mut App,
+    events: &Events,
+    widget_ctx: &mut WidgetCtx<'_>,
+    index: usize,
+    password: Option<String>,
+) {
+    let profile = widget_ctx.profile_picker.profiles[index].clone();
+    widget_ctx.profile_picker.start_connecting();
+    app.loader.start(events, async move {
+        LoadResult::Profile {
+            index: index,
+            connection: connect_profile(profile, password).await,
+        }
+    });
+}
+
+async fn connect_profile(
+    profile: ConnectionProfile,
+    password: Option<String>,
+) -> anyhow::Result<(Arc<dyn SqlClient>, DatabaseModel)> {
+    let base_db_url = profile.url(password.as_deref())?;
+    let sql_client = sql_client::connect(base_db_url, profile.read_only).await?;
+    let database_model = DatabaseModel::new(sql_client.as_ref(), profile.database.clone()).await?;
+    return Ok((sql_client, database_model));
+}
+
+/// Hands the terminal back and stops the process like Ctrl-Z does in a shell.
+/// Returns once the shell resumes it, e.g. with `fg`.
+async fn suspend_process<B: Backend + io::Write>(
+    terminal: &mut Terminal<B>,
+    events: &mut Events,
+) -> Result<(), Box<dyn Error>> {
+    events.pause_input().await;
+    suspend_terminal(terminal)?;
+    // SIGTSTP has a handler to get here, so stop with the signal that cannot have one
+    unsafe {
+        libc::raise(libc::SIGSTOP);
+    }
+    resume_terminal(terminal)?;
+    events.resume_input();
+    return Ok(());
+}
+
+fn save_sql_file(widget_ctx: &mut WidgetCtx, path: &str) {
+    match widget_ctx.sql_input.save_file(path) {
+        Ok(()) => widget_ctx.sql_output.set_file_msg("save", path),
+        Err(e) => widget_ctx
+            .sql_output
+            .set_error_msg(format!("{}: {}", path, e)),
+    }
+}
+
+/// Hands the editor's text to `$EDITOR` (or `vi`) in a temporary file with the TUI
+/// suspended, and loads the edited text back once the editor exits successfully.
+async fn edit_in_external_editor<B: Backend + io::Write>(
+    terminal: &mut Terminal<B>,
+    events: &mut Events,
+    widget_ctx: &mut WidgetCtx<'_>,
+) -> Result<(), Box<dyn Error>> {
+    // Removed when dropped, however this returns
+    let temp_file = match TempFile::create(&format!("{}\n", widget_ctx.sql_input.input())) {
+        Ok(temp_file) => temp_file,
+        Err(e) => {
+            widget_ctx
+                .sql_output
+                .set_error_msg(format!("Could not create a temporary file: {}", e));
+            return Ok(());
+        }
+    };
+    let path = &temp_file.path;
+    let editor = env::var("EDITOR").unwrap_or_else(|_| String::from("vi"));
+    // EDITOR may carry arguments, e.g. `code --wait`
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+
+    events.pause_input().await;
+    suspend_terminal(terminal)?;
+    let status = Command::new(program).args(words).arg(path).status();
+    resume_terminal(terminal)?;
+    events.resume_input();
+
+    match status {
+        Ok(status) if !status.success() => widget_ctx.sql_output.set_error_msg(format!(
+            "{} exited with {}, the text was not changed",
+            editor, status
+        )),
+        Ok(_) => match fs::read_to_string(path) {
+            Ok(text) => widget_ctx
+                .sql_input
+                .buffer
+                .set_text(text.strip_suffix('\n').unwrap_or(&text)),
+            Err(e) => widget_ctx
+                .sql_output
+                .set_error_msg(format!("{}: {}", path.display(), e)),
+        },
+        Err(e) => widget_ctx
+            .sql_output
+            .set_error_msg(format!("Could not run {}: {}", editor, e)),
+    }
+    return Ok(());
+}
+
+/// A file in the temporary directory that only this user can read, under a name nobody
+/// can guess and take first. Removed when dropped.
+struct TempFile {
+    path: PathBuf,
+}
+
+impl TempFile {
+    fn create(text: &str) -> io::Result<Self> {
+        let name = format!(
+            "sql-cli-rust-{}-{:016x}.sql",
+            std::process::id(),
+            rand::random::<u64>()
+        );
+        let path = env::temp_dir().join(name);
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&path)?;
+        // Created first, so a failed write still removes the file
+        let temp_file = Self { path: path };
+        file.write_all(text.as_bytes())?;
+        return Ok(temp_file);
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Refreshes the completion popup for the word before the cursor, and starts looking up
+/// the columns of tables in FROM/JOIN clauses that have not been looked up yet.
+fn update_completion(
+    events: &Events,
+    sql_client: &Arc<dyn SqlClient>,
+    widget_ctx: &mut WidgetCtx<'_>,
+) {
+    let (prefix, after_dot) = widget_ctx.sql_input.buffer.word_before_cursor();
+    // Only tables known to exist, so a half-typed name is never looked up
+    let tables: Vec<String> =
+        referenced_tables(&widget_ctx.sql_input.input(), widget_ctx.sql_input.dialect)
+            .iter()
+            .filter_map(|table| {
+                widget_ctx
+                    .table_list
+                    .tables
+                    .iter()
+                    .find(|name| name.eq_ignore_ascii_case(table))
+                    .cloned()
+            })
+            .collect();
+    for table in tables.iter() {
+        if widget_ctx.completion.column_cache.contains_key(table) {
+            continue;
+        }
+        // Looked up in the background for an `AppEvent::Columns` to fill in. Until then, and
+        // for good if the lookup fails, completion goes on without the table's columns
+        widget_ctx
+            .completion
+            .column_cache
+            .insert(table.clone(), vec![]);
+        let client = sql_client.clone();
+        let database = widget_ctx.database.current_database.clone();
+        let table = table.clone();
+        events.spawn(async move {
+            let columns = match client.get_table_columns(table.clone()).await {
+                Ok((headers, columns)) => TableColumnModel {
+                    headers: headers,
+                    columns: columns,
+                }
+                .column_definitions()
+                .into_iter()
+                .map(|column| column.name)
+                .collect(),
+                Err(_) => vec![],
+            };
+            AppEvent::Columns {
+                database: database,
+                table: table,
+                columns: columns,
+            }
+        });
+    }
+    widget_ctx.completion.update(
+        &prefix,
+        after_dot,
+        &tables,
+        &widget_ctx.table_list.tables,
+        &widget_ctx.database.databases,
+    );
+}
+
+/// Runs the SQL editor's text and records each statement in the query history.
+/// A script of several statements runs them in order and lists a result per statement,
+/// with the grid showing the rows of the last one that returned any.
+async fn execute_sql_input<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    events: &mut Events,
+    sql_client: &dyn SqlClient,
+    widget_ctx: &mut WidgetCtx<'_>,
+) -> Result<(), Box<dyn Error>> {
+    let statements = split_statements(&widget_ctx.sql_input.input(), sql_client.dialect());
+    if statements.is_empty() {
+        widget_ctx
+            .sql_output
+            .set_error_msg(String::from("There is no SQL to execute"));
+        return Ok(());
+    }
+    let (results, interruption) =
+        run_script(terminal, app, events, sql_client, widget_ctx, &statements).await?;
+    let mut results = match results {
+        Some(results) => results,
+        None => {
+            widget_ctx
+                .sql_output
+                .set_error_msg(interruption.unwrap_or_default());
+            widget_ctx.sql_result = None;
+            return Ok(());
+        }
+    };
+
+    let mut history_error = None;
+    for result in results.iter() {
+        let entry = HistoryEntry {
+            timestamp: chrono::Local::now(),
+            database: widget_ctx.database.current_database.clone(),
+            duration_ms: result.duration.as_millis() as u64,
+            success: result.result.is_ok(),
+            sql: result.sql.clone(),
+        };
+        if let Err(e) = widget_ctx.history_search.history.record(entry) {
+            history_error = Some(e);
+        }
+    }
+
+    if statements.len() > 1 {
+        widget_ctx
+            .sql_output
+            .set_script_msg(&results, statements.len());
+        // The grid shows the last statement that returned rows
+        widget_ctx.sql_result = results
+            .into_iter()
+            .rev()
+            .find_map(|result| match result.result {
+                Ok(QueryResult::Rows(headers, records)) => {
+                    Some(query_result_widget(headers, records))
+                }
+                _ => None,
+            });
+    } else {
+        match results.remove(0).result {
+            Ok(QueryResult::Rows(headers, records)) => {
+                widget_ctx.sql_output.set_rows_msg(records.len());
+                widget_ctx.sql_result = Some(query_result_widget(headers, records));
+            }
+            Ok(QueryResult::Summary {
+                rows_affected,
+                last_insert_id,
+            }) => {
+                widget_ctx
+                    .sql_output
+                    .set_success_msg(rows_affected, last_insert_id);
+                widget_ctx.sql_result = None;
+            }
+            Err(e) => {
+                widget_ctx.sql_output.set_error_msg(e.to_string());
+                widget_ctx.sql_result = None;
+            }
+        }
+    }
+
+    if let Some(interruption) = interruption {
+        widget_ctx.sql_output.append_error_msg(interruption);
+    }
+    if let Some(e) = history_error {
+        widget_ctx
+            .sql_output
+            .set_error_msg(format!("Could not save the query history: {}", e));
+    }
+    return Ok(());
+}
+
+/// Runs `statements` while keeping the screen alive with a spinner and the elapsed time.
+/// Ctrl-C or going over `app.statement_timeout` cancels the running statement; the reason
+/// comes back next to the results. Results are None if the backend could not interrupt
+/// the statement and it was left behind.
+async fn run_script<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    events: &mut Events,
+    sql_client: &dyn SqlClient,
+    widget_ctx: &mut WidgetCtx<'_>,
+    statements: &[String],
+) -> Result<(Option<Vec<StatementResult>>, Option<String>), Box<dyn Error>> {
+    let script = sql_client.execute_script(statements, widget_ctx.sql_input.continue_on_error);
+    tokio::pin!(script);
+    let started = Instant::now();
+    let mut interruption: Option<String> = None;
+    let mut tick = 0;
+    loop {
+        let event = tokio::select! {
+            results = &mut script => return Ok((Some(results), interruption)),
+            event = events.receive() => event,
+        };
+
+        let running = sql_client.running_statement();
+        let mut reason = None;
+        match event {
+            // Keys typed while waiting are dropped, except the one that cancels
+            AppEvent::Input(Event::Key(key)) => {
+                if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                    reason = Some(String::from("Cancelled by the user"));
+                }
+            }
+            AppEvent::Input(_) => {}
+            AppEvent::Tick => tick += 1,
+            AppEvent::Terminate => {
+                reason = Some(String::from("Cancelled to quit"));
+                events.defer(event);
+            }
+            AppEvent::Loaded { .. }
+            | AppEvent::Progress { .. }
+            | AppEvent::Columns { .. }
+            | AppEvent::Connection(_)
+            | AppEvent::Suspend => events.defer(event),
+        }
+        if let (Some(timeout), Some(running)) = (app.statement_timeout, running) {
+            if running.started.elapsed() > timeout {
+                reason = Some(format!(
+                    "Cancelled after the {}s statement timeout",
+                    timeout.as_secs()
+                ));
+            }
+        }
+        if let (Some(reason), None) = (reason, interruption.as_ref()) {
+            match sql_client.cancel_query().await {
+                Ok(true) => interruption = Some(reason),
+                Ok(false) => {
+                    return Ok((
+                        None,
+                        Some(format!(
+                            "{}; the statement cannot be interrupted on this database, its result will be ignored",
+                            reason
+                        )),
+                    ));
+                }
+                Err(e) => interruption = Some(format!("{}, but cancelling failed: {}", reason, e)),
+            }
+        }
+
+        widget_ctx.sql_output.set_running_msg(
+            tick,
+            running.map_or(0, |running| running.index),
+            statements.len(),
+            started.elapsed(),
+            interruption.is_some(),
+        );
+        terminal.draw(|f| render_layout(f, app, widget_ctx))?;
+    }
+}
+
+fn query_result_widget<'a>(
+    headers: Vec<String>,
+    records: Vec<Vec<CellValue>>,
+) -> TableRecordWdg<'a> {
+    return TableRecordWdg::new_query_result(TableRecordModel {
+        headers: headers,
+        total: records.len() as u64,
+        records: records,
+        filter: None,
+        sort: None,
+        key_columns: vec![],
+    });
+}
+
+/// Starts loading `table` with the filter remembered for it, showing it as loading until
+/// `finish_load` gets the result. If that filter no longer applies, the table loads unfiltered.
+fn start_table_load(
+    app: &mut App,
+    events: &Events,
+    sql_client: &Arc<dyn SqlClient>,
+    table: String,
+    widget_ctx: &mut WidgetCtx<'_>,
+) {
+    widget_ctx.filter_input.load(&table);
+    let filter = widget_ctx.filter_input.filter_for(&table);
+    widget_ctx
+        .table
+        .start_loading(format!("table {}", table), true);
+    let client = sql_client.clone();
+    app.loader.start(events, async move {
+        let mut filter_error = None;
+        if filter.is_some() {
+            match TableModel::new(client.as_ref(), table.clone(), filter).await {
+                Ok(table_model) => {
+                    return LoadResult::Table(Box::new(TableLoad {
+                        table: table,
+                        model: Ok(table_model),
+                        filter_error: None,
+                    }))
+                }
+                Err(e) => filter_error = Some(e),
+            }
+        }
+        LoadResult::Table(Box::new(TableLoad {
+            model: TableModel::new(client.as_ref(), table.clone(), None).await,
+            table: table,
+            filter_error: filter_error,
+        }))
+    });
+}
+
+/// Loads the shown table's first page again with `filter` and `sort` in the background.
+/// `finish_load` puts its rows in place of the loaded ones, which stay if it fails.
+fn start_records_reload(
+    app: &mut App,
+    events: &Events,
+    sql_client: &Arc<dyn SqlClient>,
+    filter: Option<String>,
+    sort: Option<RecordSort>,
+    widget_ctx: &mut WidgetCtx<'_>,
+) {
+    let record_widget = &widget_ctx.table.record_widget;
+    let table = record_widget.current_table.clone();
+    let key_columns = record_widget.table_record_model().key_columns.clone();
+    widget_ctx
+        .table
+        .start_loading(format!("table {}", table), true);
+    let client = sql_client.clone();
+    app.loader.start(events, async move {
+        LoadResult::Records {
+            records: TableRecordModel::new(
+                client.as_ref(),
+                table.clone(),
+                filter,
+                sort,
+                key_columns,
+            )
+            .await,
+            table: table,
+        }
+    });
+}
+
+/// Writes the whole of the shown table, as it is filtered and sorted, to `path` in the
+/// background, page by page with the progress in the output pane.
+fn start_table_export(
+    app: &mut App,
+    events: &Events,
+    sql_client: &Arc<dyn SqlClient>,
+    path: String,
+    format: ExportFormat,
+    overwrite: bool,
+    widget_ctx: &mut WidgetCtx<'_>,
+) {
+    let record_widget = &widget_ctx.table.record_widget;
+    let table = record_widget.current_table.clone();
+    let view = record_widget.table_record_model().view();
+    widget_ctx
+        .table
+        .start_loading(format!("table {} into {}", table, path), true);
+    let client = sql_client.clone();
+    app.loader
+        .start_with_progress(events, "Exporting", move |progress| async move {
+            let rows = export::export_table(
+                client.as_ref(),
+                &table,
+                &view,
+                format,
+                Path::new(&path),
+                overwrite,
+                |done, total| progress.report(done, total),
+            )
+            .await;
+            LoadResult::Export {
+                path: path,
+                rows: rows,
+            }
+        });
+}
+
+/// Loads the shown table's rows again as they are sorted and filtered, e.g. after a write.
+fn reload_records(
+    app: &mut App,
+    events: &Events,
+    sql_client: &Arc<dyn SqlClient>,
+    widget_ctx: &mut WidgetCtx<'_>,
+) {
+    let record_model = widget_ctx.table.record_widget.table_record_model();
+    let filter = record_model.filter.clone();
+    let sort = record_model.sort.clone();
+    start_records_reload(app, events, sql_client, filter, sort, widget_ctx);
+}
+
+/// Shows what a background load brought back; a database goes on to load its first table,
+/// and written changes reload the shown one.
+fn finish_load(
+    app: &mut App,
+    events: &Events,
+    sql_client: &Arc<dyn SqlClient>,
+    result: LoadResult,
+    widget_ctx: &mut WidgetCtx<'_>,
+) {
+    match result {
+        LoadResult::Table(load) => {
+            widget_ctx.table.finish_loading();
+            if let Some(e) = load.filter_error {
+                widget_ctx.sql_output.set_error_msg(e.to_string());
+                widget_ctx.filter_input.remember(load.table.clone(), None);
+                widget_ctx.filter_input.load(&load.table);
+            }
+            match load.model {
+                Ok(table_model) => widget_ctx
+                    .table
+                    .reset_table_widget(table_model.name.clone(), table_model),
+                Err(e) => {
+                    widget_ctx
+                        .status_bar
+                        .set_error_msg(format!("Could not load table {}: {}", load.table, e));
+                    let current_table = widget_ctx.table.record_widget.current_table.clone();
+                    widget_ctx.filter_input.load(&current_table);
+                }
+            }
+        }
+        // Only started and waited for by `pick_profile`
+        LoadResult::Profile { .. } => {}
+        LoadResult::Database { database, tables } => {
+            let tables = match tables {
+                Ok(tables) => tables,
+                Err(e) => {
+                    widget_ctx.table.finish_loading();
+                    widget_ctx
+                        .status_bar
+                        .set_error_msg(format!("Could not switch to database {}: {}", database, e));
+                    return;
+                }
+            };
+            widget_ctx.database.change_database(database.clone());
+            widget_ctx.table_list.change_tables(tables);
+            widget_ctx.table_list.change_table();
+            match widget_ctx.table_list.tables.first() {
+                Some(table) => {
+                    let table = table.clone();
+                    start_table_load(app, events, sql_client, table, widget_ctx);
+                }
+                None => {
+                    widget_ctx.table.finish_loading();
+                    widget_ctx
+                        .sql_output
+                        .set_error_msg(format!("Database {} has no tables", database));
+                }
+            }
+        }
+        LoadResult::Records { table, records } => {
+            widget_ctx.table.finish_loading();
+            match records {
+                Ok(record_model)
+                    if widget_ctx
+                        .table
+                        .record_widget
+                        .is_current_table(table.clone()) =>
+                {
+                    widget_ctx
+                        .filter_input
+                        .remember(table, record_model.filter.clone());
+                    widget_ctx.table.record_widget.replace_records(record_model);
+                }
+                Ok(_) => {}
+                Err(e) => widget_ctx.sql_output.set_error_msg(e.to_string()),
+            }
+        }
+        LoadResult::Page {
+            table,
+            offset,
+            page,
+        } => {
+            if !widget_ctx.table.record_widget.is_current_table(table) {
+                return;
+            }
+            match page {
+                Ok((headers, records)) => widget_ctx
+                    .table
+                    .record_widget
+                    .add_page(offset, headers, records),
+                Err(e) => widget_ctx.sql_output.set_error_msg(e.to_string()),
+            }
+        }
+        LoadResult::Committed { rows_affected } => {
+            widget_ctx.table.finish_loading();
+            match rows_affected {
+                Ok(rows_affected) => {
+                    widget_ctx.sql_output.set_success_msg(rows_affected, None);
+                    widget_ctx.change_set.change_set.clear();
+                    reload_records(app, events, sql_client, widget_ctx);
+                }
+                // The transaction was rolled back; keep the changes for another try.
+                Err(e) => {
+                    widget_ctx.sql_output.set_error_msg(e.to_string());
+                    app.widget_mode = WidgetMode::ReviewChanges;
+                }
+            }
+        }
+        LoadResult::Import(outcome) => {
+            widget_ctx.table.finish_loading();
+            match outcome {
+                Ok(ImportOutcome::Committed(imported)) => {
+                    widget_ctx.sql_output.set_success_msg(imported as u64, None);
+                    reload_records(app, events, sql_client, widget_ctx);
+                }
+                Ok(ImportOutcome::Failed {
+                    plan,
+                    imported,
+                    errors,
+                }) => {
+                    widget_ctx.import.report(plan, imported, errors);
+                    app.widget_mode = WidgetMode::Import;
+                }
+                Ok(ImportOutcome::RolledBack) => widget_ctx.sql_output.set_success_msg(0, None),
+                Err(e) => widget_ctx.sql_output.set_error_msg(e.to_string()),
+            }
+        }
+        LoadResult::Export { path, rows } => {
+            widget_ctx.table.finish_loading();
+            match rows {
+                Ok(rows) => widget_ctx.sql_output.set_export_msg(rows, path),
+                // Back to the prompt to confirm, unless the user went on to something else
+                Err(e)
+                    if matches!(app.widget_mode, WidgetMode::Normal)
+                        && e.downcast_ref::<io::Error>()
+                            .is_some_and(|e| e.kind() == io::ErrorKind::AlreadyExists) =>
+                {
+                    widget_ctx.export_prompt.ask_overwrite();
+                    app.widget_mode = WidgetMode::Export;
+                }
+                Err(e) => widget_ctx
+                    .sql_output
+                    .set_error_msg(format!("{}: {}", path, e)),
+            }
+        }
+    }
+}
+
+/// The mode an export was started from, to go back to once the prompt closes.
+fn export_return_mode(widget_ctx: &WidgetCtx) -> WidgetMode {
+    match widget_ctx.export_prompt.source {
+        ExportSource::Table => WidgetMode::Normal,
+        ExportSource::QueryResult => WidgetMode::EditSQL,
+    }
+}
+
+fn pending_changes_msg(widget_ctx: &WidgetCtx) -> String {
+    return format!(
+        "Commit or roll back the {} pending changes on {} first [p: review]",
+        widget_ctx.change_set.change_set.changes.len(),
+        widget_ctx.change_set.change_set.table
+    );
+}
+
 fn render_layout<B: Backend>(f: &mut Frame<'_, B>, app: &mut App, widget_ctx: &mut WidgetCtx) {
-    let size = f.size();
+    let mut size = f.size();
+    if let Some(status_bar) = widget_ctx.status_bar.widget() {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+            .split(size);
+        f.render_widget(status_bar, chunks[1]);
+        size = chunks[0];
+    }
     widget_ctx.table.record_widget.update_visible_range();
+    if let Some(sql_result) = widget_ctx.sql_result.as_mut() {
+        sql_result.update_visible_range();
+    }
 
     match app.widget_mode {
         WidgetMode::Normal => match widget_ctx.tab.mode {
@@ -206,6 +1867,30 @@ fn render_layout<B: Backend>(f: &mut Frame<'_, B>, app: &mut App, widget_ctx: &m
                 normal_layout.render_column_table_layout(f, widget_ctx);
             }
         },
+        WidgetMode::EditFilter => {
+            let normal_layout = NormalLayout::new(size);
+            normal_layout.render_edit_filter_layout(f, widget_ctx);
+        }
+        WidgetMode::EditCell => {
+            let normal_layout = NormalLayout::new(size);
+            normal_layout.render_cell_editor_layout(f, widget_ctx);
+        }
+        WidgetMode::InsertRow => {
+            let normal_layout = NormalLayout::new(size);
+            normal_layout.render_row_form_layout(f, widget_ctx);
+        }
+        WidgetMode::ReviewChanges => {
+            let normal_layout = NormalLayout::new(size);
+            normal_layout.render_change_set_layout(f, widget_ctx);
+        }
+        WidgetMode::Import => {
+            let normal_layout = NormalLayout::new(size);
+            normal_layout.render_import_layout(f, widget_ctx);
+        }
+        WidgetMode::Export => {
+            let export_layout = ExportLayout::new(size);
+            export_layout.render_layout(f, widget_ctx);
+        }
         WidgetMode::ChangeDB => {
             let change_db_layout = ChangeDBLayout::new(size);
             change_db_layout.render_layout(f, widget_ctx);
@@ -214,5 +1899,17 @@ fn render_layout<B: Backend>(f: &mut Frame<'_, B>, app: &mut App, widget_ctx: &m
             let edit_sql_layout = EditSQLLayout::new(size);
             edit_sql_layout.render_layout(f, widget_ctx);
         }
+        WidgetMode::FilePrompt => {
+            let file_prompt_layout = FilePromptLayout::new(size);
+            file_prompt_layout.render_layout(f, widget_ctx);
+        }
+        WidgetMode::HistorySearch => {
+            let history_search_layout = HistorySearchLayout::new(size);
+            history_search_layout.render_layout(f, widget_ctx);
+        }
+        WidgetMode::PickProfile => {
+            let profile_picker_layout = ProfilePickerLayout::new(size);
+            profile_picker_layout.render_layout(f, widget_ctx);
+        }
     }
 }
@@ -0,0 +1,72 @@
+use std::fmt;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+
+/// How many bytes of a binary value are shown in the grid before it is cut off.
+const BYTES_PREVIEW_LENGTH: usize = 8;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CellValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    Decimal(String),
+    Text(String),
+    Bytes(Vec<u8>),
+    Date(NaiveDate),
+    Time(NaiveTime),
+    DateTime(NaiveDateTime),
+    Timestamp(DateTime<Utc>),
+    Json(serde_json::Value),
+}
+
+impl CellValue {
+    pub fn is_null(&self) -> bool {
+        return matches!(self, CellValue::Null);
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        return matches!(
+            self,
+            CellValue::Int(_) | CellValue::UInt(_) | CellValue::Float(_) | CellValue::Decimal(_)
+        );
+    }
+
+    /// Text shown in the grid, which shortens binary values to a hex preview.
+    pub fn preview(&self) -> String {
+        match self {
+            CellValue::Bytes(bytes) if bytes.len() > BYTES_PREVIEW_LENGTH => format!(
+                "0x{}… ({} bytes)",
+                to_hex(&bytes[..BYTES_PREVIEW_LENGTH]),
+                bytes.len()
+            ),
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for CellValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CellValue::Null => write!(f, "NULL"),
+            CellValue::Bool(value) => write!(f, "{}", value),
+            CellValue::Int(value) => write!(f, "{}", value),
+            CellValue::UInt(value) => write!(f, "{}", value),
+            CellValue::Float(value) => write!(f, "{}", value),
+            CellValue::Decimal(value) => write!(f, "{}", value),
+            CellValue::Text(value) => write!(f, "{}", value),
+            CellValue::Bytes(value) => write!(f, "0x{}", to_hex(value)),
+            CellValue::Date(value) => write!(f, "{}", value),
+            CellValue::Time(value) => write!(f, "{}", value),
+            CellValue::DateTime(value) => write!(f, "{}", value),
+            CellValue::Timestamp(value) => write!(f, "{}", value),
+            CellValue::Json(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    return bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+}
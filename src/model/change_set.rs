@@ -0,0 +1,212 @@
+use crate::db::{
+    sql_client::SqlClient,
+    statement::{ColumnValue, Statement},
+};
+
+use super::cell::CellValue;
+
+/// A change to one table that has been staged but not yet written.
+pub enum PendingChange {
+    Update {
+        primary_key: Vec<ColumnValue>,
+        assignment: ColumnValue,
+        statement: Statement,
+    },
+    Insert {
+        values: Vec<ColumnValue>,
+        statement: Statement,
+    },
+    Delete {
+        primary_key: Vec<ColumnValue>,
+        statement: Statement,
+    },
+}
+
+impl PendingChange {
+    pub fn statement(&self) -> &Statement {
+        match self {
+            PendingChange::Update { statement, .. } => statement,
+            PendingChange::Insert { statement, .. } => statement,
+            PendingChange::Delete { statement, .. } => statement,
+        }
+    }
+}
+
+/// Edits, inserts and deletes on one table, applied together in a single transaction.
+pub struct ChangeSet {
+    pub table: String,
+    pub changes: Vec<PendingChange>,
+}
+
+impl ChangeSet {
+    pub fn new() -> Self {
+        Self {
+            table: String::new(),
+            changes: vec![],
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.changes.is_empty();
+    }
+
+    /// Stages an UPDATE, replacing an earlier one for the same cell.
+    pub fn stage_update(
+        &mut self,
+        sql_client: &dyn SqlClient,
+        table: &str,
+        primary_key: Vec<ColumnValue>,
+        assignment: ColumnValue,
+    ) {
+        self.changes.retain(|change| match change {
+            PendingChange::Update {
+                primary_key: key,
+                assignment: staged,
+                ..
+            } => !(staged.column == assignment.column && same_key(key, &primary_key)),
+            _ => true,
+        });
+        self.table = table.to_string();
+        self.changes.push(PendingChange::Update {
+            statement: Statement::update(sql_client, table, &assignment, &primary_key),
+            primary_key: primary_key,
+            assignment: assignment,
+        });
+    }
+
+    pub fn stage_insert(
+        &mut self,
+        sql_client: &dyn SqlClient,
+        table: &str,
+        values: Vec<ColumnValue>,
+    ) {
+        self.table = table.to_string();
+        self.changes.push(PendingChange::Insert {
+            statement: Statement::insert(sql_client, table, &values),
+            values: values,
+        });
+    }
+
+    /// Stages a DELETE, or unstages it if the row was already marked for deletion.
+    pub fn toggle_delete(
+        &mut self,
+        sql_client: &dyn SqlClient,
+        table: &str,
+        primary_key: Vec<ColumnValue>,
+    ) {
+        let staged = self.changes.iter().position(|change| match change {
+            PendingChange::Delete {
+                primary_key: key, ..
+            } => same_key(key, &primary_key),
+            _ => false,
+        });
+        if let Some(index) = staged {
+            self.changes.remove(index);
+            return;
+        }
+        self.table = table.to_string();
+        self.changes.push(PendingChange::Delete {
+            statement: Statement::delete(sql_client, table, &primary_key),
+            primary_key: primary_key,
+        });
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.changes.len() {
+            self.changes.remove(index);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.changes.clear();
+    }
+
+    pub fn statements(&self) -> Vec<Statement> {
+        return self
+            .changes
+            .iter()
+            .map(|change| change.statement().clone())
+            .collect();
+    }
+
+    /// The staged new value of `column` in the record identified by `primary_key`.
+    pub fn pending_value(&self, primary_key: &[ColumnValue], column: &str) -> Option<&CellValue> {
+        return self.changes.iter().rev().find_map(|change| match change {
+            PendingChange::Update {
+                primary_key: key,
+                assignment,
+                ..
+            } if assignment.column == column && same_key(key, primary_key) => {
+                Some(&assignment.value)
+            }
+            _ => None,
+        });
+    }
+
+    /// The staged new value of the cell at `column` in `record`, if it was edited.
+    pub fn pending_cell(
+        &self,
+        headers: &[String],
+        record: &[CellValue],
+        column: &str,
+    ) -> Option<&CellValue> {
+        return self.changes.iter().rev().find_map(|change| match change {
+            PendingChange::Update {
+                primary_key,
+                assignment,
+                ..
+            } if assignment.column == column && identifies(primary_key, headers, record) => {
+                Some(&assignment.value)
+            }
+            _ => None,
+        });
+    }
+
+    pub fn is_deleted(&self, headers: &[String], record: &[CellValue]) -> bool {
+        return self.changes.iter().any(|change| match change {
+            PendingChange::Delete { primary_key, .. } => identifies(primary_key, headers, record),
+            _ => false,
+        });
+    }
+
+    /// Staged inserts laid out as records under `headers`, with untouched columns shown as NULL.
+    pub fn inserted_records(&self, headers: &[String]) -> Vec<Vec<CellValue>> {
+        return self
+            .changes
+            .iter()
+            .filter_map(|change| match change {
+                PendingChange::Insert { values, .. } => Some(
+                    headers
+                        .iter()
+                        .map(|header| {
+                            values
+                                .iter()
+                                .find(|value| value.column == *header)
+                                .map(|value| value.value.clone())
+                                .unwrap_or(CellValue::Null)
+                        })
+                        .collect(),
+                ),
+                _ => None,
+            })
+            .collect();
+    }
+}
+
+fn same_key(a: &[ColumnValue], b: &[ColumnValue]) -> bool {
+    return a.len() == b.len()
+        && a.iter()
+            .zip(b.iter())
+            .all(|(a, b)| a.column == b.column && a.value == b.value);
+}
+
+/// Whether `record` is the row whose primary key is `primary_key`.
+fn identifies(primary_key: &[ColumnValue], headers: &[String], record: &[CellValue]) -> bool {
+    return primary_key.iter().all(|key| {
+        headers
+            .iter()
+            .position(|header| *header == key.column)
+            .and_then(|index| record.get(index))
+            == Some(&key.value)
+    });
+}
@@ -1,34 +1,35 @@
-use crate::db::sql_client::MySqlClient;
+use anyhow::Context;
 
-use super::table::TableModel;
+use crate::db::sql_client::SqlClient;
 
+#[derive(Default)]
 pub struct DatabaseModel {
     pub current_database: String,
     pub databases: Vec<String>,
-    pub current_table: TableModel,
     pub tables: Vec<String>,
 }
 
 impl DatabaseModel {
-    pub async fn new(mysql_client: &mut MySqlClient) -> Self {
-        let databases = mysql_client.get_database_list().await;
-        mysql_client.reconnect(databases[0].clone()).await;
-        let tables = mysql_client.get_table_list(databases[0].clone()).await;
-        let current_table = TableModel::new(&mysql_client, tables[0].clone()).await;
+    /// Opens `database`, or the client's default one if None, or else the first one, and
+    /// lists its tables, leaving them to be loaded on screen.
+    pub async fn new(sql_client: &dyn SqlClient, database: Option<String>) -> anyhow::Result<Self> {
+        let databases = sql_client.get_database_list().await?;
+        let database = match database.or_else(|| sql_client.default_database()) {
+            Some(database) if databases.contains(&database) => database,
+            Some(database) => anyhow::bail!("The server has no database {}", database),
+            None => databases
+                .first()
+                .context("The server has no databases")?
+                .clone(),
+        };
+        sql_client.reconnect(database.clone()).await?;
+        // An empty database still opens, with the tables left for the user to create
+        let tables = sql_client.get_table_list(database.clone()).await?;
 
-        Self {
-            current_database: databases[0].clone(),
-            current_table: current_table,
+        Ok(Self {
+            current_database: database,
             databases: databases,
             tables: tables,
-        }
-    }
-
-    pub async fn set_default_database(&self, mysql_client: &mut MySqlClient) {
-        mysql_client.reconnect(self.databases[0].clone()).await;
-    }
-
-    pub async fn change_database(&self, mysql_client: &mut MySqlClient, new_database: String) {
-        mysql_client.reconnect(new_database).await;
+        })
     }
 }
@@ -1,2 +1,4 @@
+pub mod cell;
+pub mod change_set;
 pub mod database;
 pub mod table;
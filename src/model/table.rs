@@ -1,4 +1,8 @@
-use crate::db::sql_client::MySqlClient;
+use std::{future::Future, sync::Arc};
+
+use crate::db::{sql_client::SqlClient, statement::ColumnValue};
+
+use super::cell::CellValue;
 
 pub struct TableModel {
     pub name: String,
@@ -6,34 +10,335 @@ pub struct TableModel {
     pub column: TableColumnModel,
 }
 
+pub const RECORD_PAGE_SIZE: usize = 200;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+#[derive(Clone)]
+pub struct RecordSort {
+    pub column: String,
+    pub order: SortOrder,
+}
+
+#[derive(Default)]
 pub struct TableRecordModel {
     pub headers: Vec<String>,
-    pub records: Vec<Vec<String>>,
+    pub records: Vec<Vec<CellValue>>,
+    pub total: u64,
+    pub filter: Option<String>,
+    pub sort: Option<RecordSort>,
+    /// What pages are ordered by after `sort`, see `TableColumnModel::key_columns`.
+    pub key_columns: Vec<String>,
+}
+
+/// One row of `TableColumnModel`, as needed to fill in a new record.
+#[derive(Clone)]
+pub struct ColumnDefinition {
+    pub name: String,
+    pub column_type: String,
+    pub nullable: bool,
+    pub default: Option<String>,
 }
 
+#[derive(Default)]
 pub struct TableColumnModel {
     pub headers: Vec<String>,
-    pub columns: Vec<Vec<String>>,
+    pub columns: Vec<Vec<CellValue>>,
 }
 
 impl TableModel {
-    pub async fn new(mysql_client: &MySqlClient, table: String) -> Self {
-        let (record_headers, record_fields) = mysql_client.get_table_records(table.clone()).await;
-        let table_record = TableRecordModel {
-            headers: record_headers,
-            records: record_fields,
-        };
-
-        let (column_headers, column_fields) = mysql_client.get_table_columns(table.clone()).await;
+    pub async fn new(
+        sql_client: &dyn SqlClient,
+        table: String,
+        filter: Option<String>,
+    ) -> anyhow::Result<Self> {
+        let (column_headers, column_fields) = sql_client.get_table_columns(table.clone()).await?;
         let table_column = TableColumnModel {
             headers: column_headers,
             columns: column_fields,
         };
+        let table_record = TableRecordModel::new(
+            sql_client,
+            table.clone(),
+            filter,
+            None,
+            table_column.key_columns(),
+        )
+        .await?;
 
-        Self {
+        Ok(Self {
             name: table,
             record: table_record,
             column: table_column,
+        })
+    }
+}
+
+impl TableColumnModel {
+    fn field(&self, column: &[CellValue], header: &str) -> Option<String> {
+        let index = self.headers.iter().position(|h| h == header)?;
+        return column.get(index).map(|value| value.to_string());
+    }
+
+    pub fn column_definitions(&self) -> Vec<ColumnDefinition> {
+        return self
+            .columns
+            .iter()
+            .filter_map(|column| {
+                Some(ColumnDefinition {
+                    name: self.field(column, "Field")?,
+                    column_type: self.field(column, "Type").unwrap_or_default(),
+                    nullable: self.field(column, "Null").as_deref() == Some("YES"),
+                    default: self
+                        .headers
+                        .iter()
+                        .position(|h| h == "Default")
+                        .and_then(|index| column.get(index))
+                        .filter(|value| !value.is_null())
+                        .map(|value| value.to_string()),
+                })
+            })
+            .collect();
+    }
+
+    /// The declared type of `column_name`, e.g. `varchar(255)`.
+    pub fn column_type(&self, column_name: &str) -> String {
+        return self
+            .columns
+            .iter()
+            .find(|column| self.field(column, "Field").as_deref() == Some(column_name))
+            .and_then(|column| self.field(column, "Type"))
+            .unwrap_or_default();
+    }
+
+    pub fn primary_key_columns(&self) -> Vec<String> {
+        return self
+            .columns
+            .iter()
+            .filter(|column| self.field(column, "Key").as_deref() == Some("PRI"))
+            .filter_map(|column| self.field(column, "Field"))
+            .collect();
+    }
+
+    /// The columns that order rows completely, to break ties between pages: the primary
+    /// key, or else one column with a unique index of its own, NOT NULL ones first. A table
+    /// with neither has none, so its paging order is not stable: rows that tie on the sort
+    /// may repeat or be skipped between pages. Ordering by every column instead would sort
+    /// the whole table for each page.
+    pub fn key_columns(&self) -> Vec<String> {
+        let primary_key = self.primary_key_columns();
+        if !primary_key.is_empty() {
+            return primary_key;
+        }
+        let mut unique_columns: Vec<&Vec<CellValue>> = self
+            .columns
+            .iter()
+            .filter(|column| self.field(column, "Key").as_deref() == Some("UNI"))
+            .collect();
+        unique_columns.sort_by_key(|column| self.field(column, "Null").as_deref() != Some("NO"));
+        return unique_columns
+            .first()
+            .and_then(|column| self.field(column, "Field"))
+            .into_iter()
+            .collect();
+    }
+
+    /// Pairs each primary key column with its value in `record`.
+    pub fn primary_key(&self, headers: &[String], record: &[CellValue]) -> Vec<ColumnValue> {
+        return self
+            .primary_key_columns()
+            .into_iter()
+            .filter_map(|column| {
+                let index = headers.iter().position(|h| *h == column)?;
+                Some(ColumnValue {
+                    column_type: self.column_type(&column),
+                    value: record.get(index)?.clone(),
+                    column: column,
+                })
+            })
+            .collect();
+    }
+}
+
+/// The name of a column type without its length, precision or MySQL attributes, in lower
+/// case, e.g. `int` for `INT(11) UNSIGNED` and `character varying` for `character varying(20)`.
+pub fn base_type(column_type: &str) -> String {
+    let name = column_type
+        .split('(')
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    return name
+        .split_whitespace()
+        .filter(|word| !matches!(*word, "unsigned" | "signed" | "zerofill"))
+        .collect::<Vec<&str>>()
+        .join(" ");
+}
+
+impl TableRecordModel {
+    pub async fn new(
+        sql_client: &dyn SqlClient,
+        table: String,
+        filter: Option<String>,
+        sort: Option<RecordSort>,
+        key_columns: Vec<String>,
+    ) -> Result<Self, sqlx::Error> {
+        let (headers, records) = sql_client
+            .get_table_records(
+                table.clone(),
+                filter.as_deref(),
+                sort.as_ref(),
+                &key_columns,
+                RECORD_PAGE_SIZE,
+                0,
+            )
+            .await?;
+        let total = sql_client
+            .get_table_record_count(table, filter.as_deref())
+            .await?;
+
+        Ok(Self {
+            headers: headers,
+            records: records,
+            total: total,
+            filter: filter,
+            sort: sort,
+            key_columns: key_columns,
+        })
+    }
+
+    /// The same headers, filter, sort and count without the rows, e.g. to page through
+    /// the table again in the background.
+    pub fn view(&self) -> Self {
+        Self {
+            headers: self.headers.clone(),
+            records: vec![],
+            total: self.total,
+            filter: self.filter.clone(),
+            sort: self.sort.clone(),
+            key_columns: self.key_columns.clone(),
+        }
+    }
+
+    pub fn is_fully_loaded(&self) -> bool {
+        return self.records.len() as u64 >= self.total;
+    }
+
+    /// Fetches the page after the loaded rows for `add_page`. The future owns what it
+    /// needs, so it can run in the background while the loaded rows stay on screen.
+    pub fn fetch_next_page(
+        &self,
+        sql_client: Arc<dyn SqlClient>,
+        table: String,
+    ) -> impl Future<Output = Result<(Vec<String>, Vec<Vec<CellValue>>), sqlx::Error>> + Send + 'static
+    {
+        let filter = self.filter.clone();
+        let sort = self.sort.clone();
+        let key_columns = self.key_columns.clone();
+        let offset = self.records.len();
+        return async move {
+            sql_client
+                .get_table_records(
+                    table,
+                    filter.as_deref(),
+                    sort.as_ref(),
+                    &key_columns,
+                    RECORD_PAGE_SIZE,
+                    offset,
+                )
+                .await
+        };
+    }
+
+    /// Appends a page fetched from row `offset`, unless other rows were loaded since.
+    pub fn add_page(&mut self, offset: usize, headers: Vec<String>, records: Vec<Vec<CellValue>>) {
+        if offset != self.records.len() {
+            return;
+        }
+        if self.headers.is_empty() {
+            self.headers = headers;
         }
+        if records.is_empty() {
+            // Rows were deleted since the count was taken.
+            self.total = self.records.len() as u64;
+        }
+        self.records.extend(records);
+    }
+
+    /// The sort after the next step on `column` through ascending, descending and unsorted.
+    pub fn next_sort(&self, column: String) -> Option<RecordSort> {
+        return match &self.sort {
+            Some(sort) if sort.column == column && sort.order == SortOrder::Asc => {
+                Some(RecordSort {
+                    column: column,
+                    order: SortOrder::Desc,
+                })
+            }
+            Some(sort) if sort.column == column => None,
+            _ => Some(RecordSort {
+                column: column,
+                order: SortOrder::Asc,
+            }),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{base_type, TableColumnModel};
+    use crate::model::cell::CellValue;
+
+    fn columns(rows: &[(&str, &str, &str)]) -> TableColumnModel {
+        return TableColumnModel {
+            headers: vec![
+                String::from("Field"),
+                String::from("Type"),
+                String::from("Key"),
+            ],
+            columns: rows
+                .iter()
+                .map(|(field, column_type, key)| {
+                    vec![
+                        CellValue::Text(field.to_string()),
+                        CellValue::Text(column_type.to_string()),
+                        CellValue::Text(key.to_string()),
+                    ]
+                })
+                .collect(),
+        };
+    }
+
+    #[test]
+    fn base_type_drops_length_and_attributes() {
+        assert_eq!(base_type("INT(11) UNSIGNED"), "int");
+        assert_eq!(base_type("bigint unsigned zerofill"), "bigint");
+        assert_eq!(base_type("character varying(20)"), "character varying");
+        assert_eq!(base_type("interval"), "interval");
+    }
+
+    #[test]
+    fn key_columns_prefer_the_primary_key() {
+        let model = columns(&[("name", "text", ""), ("id", "integer", "PRI")]);
+        assert_eq!(model.key_columns(), vec![String::from("id")]);
+    }
+
+    #[test]
+    fn key_columns_fall_back_to_a_unique_column() {
+        let model = columns(&[
+            ("name", "text", ""),
+            ("code", "text", "UNI"),
+            ("email", "text", "UNI"),
+        ]);
+        assert_eq!(model.key_columns(), vec![String::from("code")]);
+    }
+
+    #[test]
+    fn key_columns_are_empty_without_a_key() {
+        let model = columns(&[("name", "text", ""), ("doc", "json", "MUL")]);
+        assert!(model.key_columns().is_empty());
     }
 }
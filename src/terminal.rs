@@ -0,0 +1,88 @@
+use std::{
+    error::Error,
+    io::{self, Stdout},
+    panic, process, thread,
+};
+
+use crossterm::{
+    cursor::Show,
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use tui::{
+    backend::{Backend, CrosstermBackend},
+    Terminal,
+};
+
+pub type AppTerminal = Terminal<CrosstermBackend<Stdout>>;
+
+/// Holds the terminal in raw mode on the alternate screen with mouse capture while the app
+/// runs, and gives it back to the shell when dropped or when anything panics.
+pub struct TerminalGuard;
+
+impl TerminalGuard {
+    pub fn new() -> Result<(Self, AppTerminal), Box<dyn Error>> {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore_terminal();
+            // The message goes to the shell's screen, not the alternate one that is gone
+            default_hook(info);
+            // A background task's panic would otherwise leave the app running without a screen
+            if thread::current().name() != Some("main") {
+                process::exit(101);
+            }
+        }));
+
+        enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+        let terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+        return Ok((Self, terminal));
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+/// Undoes what `TerminalGuard::new` set up. Safe to call more than once.
+fn restore_terminal() {
+    let _ = disable_raw_mode();
+    let _ = execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        Show
+    );
+}
+
+/// Gives the terminal back to the shell the way `TerminalGuard` does on exit.
+pub fn suspend_terminal<B: Backend + io::Write>(
+    terminal: &mut Terminal<B>,
+) -> Result<(), Box<dyn Error>> {
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
+    terminal.show_cursor()?;
+    return Ok(());
+}
+
+/// Takes the terminal back after `suspend_terminal` and forces a full redraw.
+pub fn resume_terminal<B: Backend + io::Write>(
+    terminal: &mut Terminal<B>,
+) -> Result<(), Box<dyn Error>> {
+    enable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableMouseCapture
+    )?;
+    terminal.clear()?;
+    return Ok(());
+}
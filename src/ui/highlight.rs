@@ -0,0 +1,74 @@
+use tui::{
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+};
+
+use crate::db::lexer::{tokenize, unbalanced_parens, Dialect, Token, TokenKind};
+
+/// Style for each character of `sql`, newlines included.
+/// Unterminated literals and comments and unmatched parentheses are shown in red.
+pub fn char_styles(sql: &str, dialect: Dialect) -> Vec<Style> {
+    let tokens = tokenize(sql, dialect);
+    let unbalanced = unbalanced_parens(sql, &tokens);
+    let mut styles = vec![];
+    for (index, token) in tokens.iter().enumerate() {
+        let style = if unbalanced.contains(&index) {
+            error_style()
+        } else {
+            token_style(token)
+        };
+        styles.extend(token.text(sql).chars().map(|_| style));
+    }
+    return styles;
+}
+
+/// `sql` as highlighted lines, for places that show SQL without editing it.
+pub fn highlight(sql: &str, dialect: Dialect) -> Vec<Spans<'static>> {
+    let mut lines = vec![];
+    let mut spans: Vec<Span> = vec![];
+    let mut text = String::new();
+    let mut style = Style::default();
+    for (c, char_style) in sql.chars().zip(char_styles(sql, dialect)) {
+        if c == '\n' || char_style != style {
+            if !text.is_empty() {
+                spans.push(Span::styled(std::mem::take(&mut text), style));
+            }
+            style = char_style;
+        }
+        if c == '\n' {
+            lines.push(Spans::from(std::mem::take(&mut spans)));
+        } else {
+            text.push(c);
+        }
+    }
+    if !text.is_empty() {
+        spans.push(Span::styled(text, style));
+    }
+    lines.push(Spans::from(spans));
+    return lines;
+}
+
+fn token_style(token: &Token) -> Style {
+    if !token.terminated {
+        return error_style();
+    }
+    return match token.kind {
+        TokenKind::Keyword => Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD),
+        TokenKind::String => Style::default().fg(Color::Green),
+        TokenKind::Number => Style::default().fg(Color::Magenta),
+        TokenKind::Comment => Style::default()
+            .fg(Color::DarkGray)
+            .add_modifier(Modifier::ITALIC),
+        TokenKind::Operator => Style::default().fg(Color::Yellow),
+        TokenKind::Identifier | TokenKind::Whitespace => Style::default(),
+    };
+}
+
+fn error_style() -> Style {
+    return Style::default()
+        .fg(Color::White)
+        .bg(Color::Red)
+        .add_modifier(Modifier::BOLD);
+}
@@ -45,14 +45,24 @@ impl LayoutTrait for ChangeDBLayout {
             &mut widget_ctx.database.database_select_state,
         );
 
-        f.render_widget(widget_ctx.sql_input.widget(), self.main_widget[0]);
+        f.render_widget(
+            widget_ctx.sql_input.widget(self.main_widget[0]),
+            self.main_widget[0],
+        );
 
         f.render_widget(widget_ctx.tab.widget(), self.main_widget[1]);
 
+        if let Some(loading) = widget_ctx.table.loading_widget() {
+            f.render_widget(loading, self.main_widget[2]);
+            return;
+        }
         match widget_ctx.tab.mode {
             TableMode::Records => {
                 f.render_stateful_widget(
-                    widget_ctx.table.record_widget.widget(),
+                    widget_ctx
+                        .table
+                        .record_widget
+                        .widget(Some(&widget_ctx.change_set.change_set)),
                     self.main_widget[2],
                     &mut widget_ctx.table.record_widget.select_row_list_state,
                 );
@@ -1,11 +1,11 @@
 use tui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
+    widgets::Clear,
     Frame,
 };
 
 use crate::ui::widgets::ctx::WidgetCtx;
-use unicode_width::UnicodeWidthStr;
 
 use super::layout_trait::LayoutTrait;
 
@@ -28,7 +28,7 @@ impl LayoutTrait for EditSQLLayout {
 
         let chunks_2 = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+            .constraints([Constraint::Length(10), Constraint::Min(0)].as_ref())
             .split(chunks[1]);
 
         Self {
@@ -49,14 +49,55 @@ impl LayoutTrait for EditSQLLayout {
             &mut widget_ctx.table_list.table_select_state,
         );
 
-        f.render_widget(widget_ctx.sql_input.widget(), self.main_widget[0]);
-        f.set_cursor(
-            // Put cursor past the end of the input text
-            self.main_widget[0].x + widget_ctx.sql_input.input.width() as u16 + 1,
-            // Move one line down, from the border to the input line
-            self.main_widget[0].y + 1,
+        f.render_widget(
+            widget_ctx.sql_input.widget(self.main_widget[0]),
+            self.main_widget[0],
         );
+        let (cursor_x, cursor_y) = widget_ctx.sql_input.cursor_position(self.main_widget[0]);
+        f.set_cursor(cursor_x, cursor_y);
+
+        match widget_ctx.sql_result.as_mut() {
+            Some(sql_result) => {
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints(
+                        [
+                            Constraint::Min(0),
+                            Constraint::Length(widget_ctx.sql_output.height().clamp(4, 12)),
+                        ]
+                        .as_ref(),
+                    )
+                    .split(self.main_widget[1]);
 
-        f.render_widget(widget_ctx.sql_output.widget(), self.main_widget[1]);
+                f.render_stateful_widget(
+                    sql_result.widget(None),
+                    chunks[0],
+                    &mut sql_result.select_row_list_state,
+                );
+                f.render_widget(widget_ctx.sql_output.widget(), chunks[1]);
+            }
+            None => {
+                f.render_widget(widget_ctx.sql_output.widget(), self.main_widget[1]);
+            }
+        }
+
+        if widget_ctx.completion.is_visible() {
+            // Below the cursor, moved left if it would run off the screen
+            let size = f.size();
+            let (width, height) = widget_ctx.completion.size();
+            let width = width.min(size.width);
+            let popup = Rect {
+                x: cursor_x.min(size.width - width),
+                y: cursor_y + 1,
+                width: width,
+                height: height.min(size.height.saturating_sub(cursor_y + 1)),
+            };
+            f.render_widget(Clear, popup);
+            f.render_stateful_widget(
+                widget_ctx.completion.widget(),
+                popup,
+                &mut widget_ctx.completion.select_state,
+            );
+        }
     }
 }
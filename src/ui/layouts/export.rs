@@ -0,0 +1,49 @@
+use tui::{backend::Backend, layout::Rect, widgets::Clear, Frame};
+
+use crate::ui::widgets::{ctx::WidgetCtx, export_prompt::ExportSource};
+use unicode_width::UnicodeWidthStr;
+
+use super::{
+    edit_sql::EditSQLLayout,
+    layout_trait::{LayoutTrait, NormalLayoutTrait},
+    normal::NormalLayout,
+    popup::centered_rect,
+};
+
+/// The export prompt on top of the screen the export was started from.
+pub struct ExportLayout {
+    size: Rect,
+}
+
+impl LayoutTrait for ExportLayout {
+    fn new(size: Rect) -> Self {
+        Self { size: size }
+    }
+
+    fn render_layout<B: Backend>(&self, f: &mut Frame<'_, B>, widget_ctx: &mut WidgetCtx) {
+        match widget_ctx.export_prompt.source {
+            ExportSource::Table => {
+                NormalLayout::new(self.size).render_record_table_layout(f, widget_ctx);
+            }
+            ExportSource::QueryResult => {
+                EditSQLLayout::new(self.size).render_layout(f, widget_ctx);
+            }
+        }
+
+        let popup = centered_rect(60, 20, self.size);
+        let popup = Rect {
+            height: popup.height.max(5),
+            ..popup
+        };
+        f.render_widget(Clear, popup);
+        f.render_widget(widget_ctx.export_prompt.widget(), popup);
+        f.set_cursor(
+            // Put cursor past the end of the path on the second line
+            popup.x
+                + (widget_ctx.export_prompt.path_offset() + widget_ctx.export_prompt.input.width())
+                    as u16
+                + 1,
+            popup.y + 2,
+        );
+    }
+}
@@ -0,0 +1,37 @@
+use tui::{backend::Backend, layout::Rect, widgets::Clear, Frame};
+
+use crate::ui::widgets::ctx::WidgetCtx;
+use unicode_width::UnicodeWidthStr;
+
+use super::{edit_sql::EditSQLLayout, layout_trait::LayoutTrait, popup::centered_rect};
+
+/// The open/save prompt on top of the SQL editor.
+pub struct FilePromptLayout {
+    size: Rect,
+}
+
+impl LayoutTrait for FilePromptLayout {
+    fn new(size: Rect) -> Self {
+        Self { size: size }
+    }
+
+    fn render_layout<B: Backend>(&self, f: &mut Frame<'_, B>, widget_ctx: &mut WidgetCtx) {
+        EditSQLLayout::new(self.size).render_layout(f, widget_ctx);
+
+        let popup = centered_rect(60, 20, self.size);
+        let popup = Rect {
+            height: popup.height.max(4),
+            ..popup
+        };
+        f.render_widget(Clear, popup);
+        f.render_widget(widget_ctx.file_prompt.widget(), popup);
+        f.set_cursor(
+            // Put cursor past the end of the path on the first line
+            popup.x
+                + (widget_ctx.file_prompt.path_offset() + widget_ctx.file_prompt.input.width())
+                    as u16
+                + 1,
+            popup.y + 1,
+        );
+    }
+}
@@ -0,0 +1,47 @@
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    widgets::Clear,
+    Frame,
+};
+
+use crate::ui::widgets::ctx::WidgetCtx;
+use unicode_width::UnicodeWidthStr;
+
+use super::{edit_sql::EditSQLLayout, layout_trait::LayoutTrait, popup::centered_rect};
+
+/// The history search popup on top of the SQL editor.
+pub struct HistorySearchLayout {
+    size: Rect,
+}
+
+impl LayoutTrait for HistorySearchLayout {
+    fn new(size: Rect) -> Self {
+        Self { size: size }
+    }
+
+    fn render_layout<B: Backend>(&self, f: &mut Frame<'_, B>, widget_ctx: &mut WidgetCtx) {
+        EditSQLLayout::new(self.size).render_layout(f, widget_ctx);
+
+        let popup = centered_rect(80, 60, self.size);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+            .split(popup);
+        f.render_widget(Clear, popup);
+        f.render_widget(widget_ctx.history_search.query_widget(), chunks[0]);
+        f.render_stateful_widget(
+            widget_ctx.history_search.list_widget(),
+            chunks[1],
+            &mut widget_ctx.history_search.select_state,
+        );
+        f.set_cursor(
+            // Put cursor past the end of the search input
+            chunks[0].x
+                + (widget_ctx.history_search.query_offset()
+                    + widget_ctx.history_search.query.width()) as u16
+                + 1,
+            chunks[0].y + 1,
+        );
+    }
+}
@@ -14,6 +14,23 @@ pub trait NormalLayoutTrait {
         f: &mut Frame<'_, B>,
         widget_ctx: &mut WidgetCtx,
     );
+    fn render_edit_filter_layout<B: Backend>(
+        &self,
+        f: &mut Frame<'_, B>,
+        widget_ctx: &mut WidgetCtx,
+    );
+    fn render_cell_editor_layout<B: Backend>(
+        &self,
+        f: &mut Frame<'_, B>,
+        widget_ctx: &mut WidgetCtx,
+    );
+    fn render_row_form_layout<B: Backend>(&self, f: &mut Frame<'_, B>, widget_ctx: &mut WidgetCtx);
+    fn render_change_set_layout<B: Backend>(
+        &self,
+        f: &mut Frame<'_, B>,
+        widget_ctx: &mut WidgetCtx,
+    );
+    fn render_import_layout<B: Backend>(&self, f: &mut Frame<'_, B>, widget_ctx: &mut WidgetCtx);
     fn render_column_table_layout<B: Backend>(
         &self,
         f: &mut Frame<'_, B>,
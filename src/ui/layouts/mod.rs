@@ -1,4 +1,9 @@
 pub mod change_db;
 pub mod edit_sql;
+pub mod export;
+pub mod file_prompt;
+pub mod history_search;
 pub mod layout_trait;
 pub mod normal;
+pub mod popup;
+pub mod profile_picker;
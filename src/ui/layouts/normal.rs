@@ -1,12 +1,14 @@
 use tui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
+    widgets::Clear,
     Frame,
 };
 
-use crate::ui::widgets::ctx::WidgetCtx;
+use crate::ui::widgets::{ctx::WidgetCtx, import::ImportPhase};
+use unicode_width::UnicodeWidthStr;
 
-use super::layout_trait::NormalLayoutTrait;
+use super::{layout_trait::NormalLayoutTrait, popup::centered_rect};
 
 pub struct NormalLayout {
     left_side_widget: Vec<Rect>,
@@ -29,9 +31,11 @@ impl NormalLayoutTrait for NormalLayout {
             .direction(Direction::Vertical)
             .constraints(
                 [
+                    Constraint::Length(3),
                     Constraint::Length(3),
                     Constraint::Length(3),
                     Constraint::Min(0),
+                    Constraint::Length(4),
                 ]
                 .as_ref(),
             )
@@ -50,13 +54,95 @@ impl NormalLayoutTrait for NormalLayout {
     ) {
         self.render_base_layout(f, widget_ctx);
 
+        if let Some(loading) = widget_ctx.table.loading_widget() {
+            f.render_widget(loading, self.main_widget[3]);
+            return;
+        }
         f.render_stateful_widget(
-            widget_ctx.table.record_widget.widget(),
-            self.main_widget[2],
+            widget_ctx
+                .table
+                .record_widget
+                .widget(Some(&widget_ctx.change_set.change_set)),
+            self.main_widget[3],
             &mut widget_ctx.table.record_widget.select_row_list_state,
         );
     }
 
+    fn render_edit_filter_layout<B: Backend>(
+        &self,
+        f: &mut Frame<'_, B>,
+        widget_ctx: &mut WidgetCtx,
+    ) {
+        self.render_record_table_layout(f, widget_ctx);
+
+        f.set_cursor(
+            // Put cursor past the end of the filter text
+            self.main_widget[1].x + widget_ctx.filter_input.input.width() as u16 + 1,
+            // Move one line down, from the border to the input line
+            self.main_widget[1].y + 1,
+        );
+    }
+
+    fn render_cell_editor_layout<B: Backend>(
+        &self,
+        f: &mut Frame<'_, B>,
+        widget_ctx: &mut WidgetCtx,
+    ) {
+        self.render_record_table_layout(f, widget_ctx);
+
+        let popup = centered_rect(60, 30, self.main_widget[3]);
+        f.render_widget(Clear, popup);
+        f.render_widget(widget_ctx.cell_editor.widget(), popup);
+        f.set_cursor(
+            // Put cursor past the end of the new value on the second line
+            popup.x + widget_ctx.cell_editor.input_width() as u16 + 1,
+            popup.y + 2,
+        );
+    }
+
+    fn render_row_form_layout<B: Backend>(&self, f: &mut Frame<'_, B>, widget_ctx: &mut WidgetCtx) {
+        self.render_record_table_layout(f, widget_ctx);
+
+        let popup = centered_rect(80, 80, self.main_widget[3]);
+        f.render_widget(Clear, popup);
+        f.render_widget(widget_ctx.row_form.widget(popup.height), popup);
+        let (x, y) = widget_ctx.row_form.cursor_position(popup.height);
+        f.set_cursor(popup.x + x, popup.y + y);
+    }
+
+    fn render_change_set_layout<B: Backend>(
+        &self,
+        f: &mut Frame<'_, B>,
+        widget_ctx: &mut WidgetCtx,
+    ) {
+        self.render_record_table_layout(f, widget_ctx);
+
+        let popup = centered_rect(90, 80, self.main_widget[3]);
+        f.render_widget(Clear, popup);
+        f.render_stateful_widget(
+            widget_ctx.change_set.widget(),
+            popup,
+            &mut widget_ctx.change_set.select_state,
+        );
+    }
+
+    fn render_import_layout<B: Backend>(&self, f: &mut Frame<'_, B>, widget_ctx: &mut WidgetCtx) {
+        self.render_record_table_layout(f, widget_ctx);
+
+        let popup = centered_rect(80, 80, self.main_widget[3]);
+        f.render_widget(Clear, popup);
+        f.render_widget(widget_ctx.import.widget(), popup);
+        if let ImportPhase::Path = widget_ctx.import.phase {
+            f.set_cursor(
+                // Put cursor past the end of the path
+                popup.x
+                    + (widget_ctx.import.path_offset() + widget_ctx.import.input.width()) as u16
+                    + 1,
+                popup.y + 1,
+            );
+        }
+    }
+
     fn render_column_table_layout<B: Backend>(
         &self,
         f: &mut Frame<'_, B>,
@@ -64,9 +150,13 @@ impl NormalLayoutTrait for NormalLayout {
     ) {
         self.render_base_layout(f, widget_ctx);
 
+        if let Some(loading) = widget_ctx.table.loading_widget() {
+            f.render_widget(loading, self.main_widget[3]);
+            return;
+        }
         f.render_stateful_widget(
             widget_ctx.table.column_widget.widget(),
-            self.main_widget[2],
+            self.main_widget[3],
             &mut widget_ctx.table.column_widget.select_row_list_state,
         );
     }
@@ -83,8 +173,15 @@ impl NormalLayoutTrait for NormalLayout {
             &mut widget_ctx.table_list.table_select_state,
         );
 
-        f.render_widget(widget_ctx.sql_input.widget(), self.main_widget[0]);
+        f.render_widget(
+            widget_ctx.sql_input.widget(self.main_widget[0]),
+            self.main_widget[0],
+        );
+
+        f.render_widget(widget_ctx.filter_input.widget(), self.main_widget[1]);
+
+        f.render_widget(widget_ctx.tab.widget(), self.main_widget[2]);
 
-        f.render_widget(widget_ctx.tab.widget(), self.main_widget[1]);
+        f.render_widget(widget_ctx.sql_output.widget(), self.main_widget[4]);
     }
 }
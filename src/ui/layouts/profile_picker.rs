@@ -0,0 +1,49 @@
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    widgets::Clear,
+    Frame,
+};
+
+use crate::ui::widgets::{ctx::WidgetCtx, profile_picker::PickerPhase};
+
+use super::{
+    layout_trait::{LayoutTrait, NormalLayoutTrait},
+    normal::NormalLayout,
+    popup::centered_rect,
+};
+
+/// The connection picker on top of the table view, which is empty before the first connection.
+pub struct ProfilePickerLayout {
+    size: Rect,
+}
+
+impl LayoutTrait for ProfilePickerLayout {
+    fn new(size: Rect) -> Self {
+        Self { size: size }
+    }
+
+    fn render_layout<B: Backend>(&self, f: &mut Frame<'_, B>, widget_ctx: &mut WidgetCtx) {
+        NormalLayout::new(self.size).render_record_table_layout(f, widget_ctx);
+
+        let popup = centered_rect(60, 50, self.size);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(3)].as_ref())
+            .split(popup);
+        f.render_widget(Clear, popup);
+        f.render_stateful_widget(
+            widget_ctx.profile_picker.list_widget(),
+            chunks[0],
+            &mut widget_ctx.profile_picker.select_state,
+        );
+        f.render_widget(widget_ctx.profile_picker.status_widget(), chunks[1]);
+        if widget_ctx.profile_picker.phase == PickerPhase::Password {
+            f.set_cursor(
+                // Put cursor past the end of the masked password
+                chunks[1].x + widget_ctx.profile_picker.password_offset() as u16 + 1,
+                chunks[1].y + 1,
+            );
+        }
+    }
+}
@@ -1,2 +1,4 @@
+pub mod highlight;
 pub mod layouts;
+pub mod spinner;
 pub mod widgets;
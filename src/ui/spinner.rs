@@ -0,0 +1,6 @@
+const FRAMES: [char; 4] = ['|', '/', '-', '\\'];
+
+/// The spinner character to show on the `tick`th redraw.
+pub fn spinner_frame(tick: usize) -> char {
+    return FRAMES[tick % FRAMES.len()];
+}
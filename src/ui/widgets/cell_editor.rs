@@ -0,0 +1,163 @@
+use tui::{
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::{db::statement::ColumnValue, model::cell::CellValue};
+
+/// The record cell being edited, identified by the row's primary key.
+pub struct CellEdit {
+    pub table: String,
+    pub target: ColumnValue,
+    pub primary_key: Vec<ColumnValue>,
+}
+
+pub struct CellEditorWdg<'a> {
+    title: &'a str,
+    pub input: String,
+    /// Set with ctrl-n, assigns NULL instead of the input.
+    pub is_null: bool,
+    pub cell_edit: Option<CellEdit>,
+}
+
+impl<'a> CellEditorWdg<'a> {
+    pub fn new() -> Self {
+        Self {
+            title: "Edit cell [ctrl-n: NULL] [enter: stage UPDATE] [esc: cancel]",
+            input: String::new(),
+            is_null: false,
+            cell_edit: None,
+        }
+    }
+
+    pub fn widget(&self) -> Paragraph<'a> {
+        let lines = match &self.cell_edit {
+            Some(cell_edit) => vec![
+                Spans::from(Span::styled(
+                    format!(
+                        "{} ({}) was: {}",
+                        cell_edit.target.column,
+                        cell_edit.target.column_type,
+                        cell_edit.target.value.preview()
+                    ),
+                    Style::default().add_modifier(Modifier::DIM),
+                )),
+                if self.is_null {
+                    Spans::from(Span::styled("NULL", Style::default().fg(Color::DarkGray)))
+                } else {
+                    Spans::from(Span::from(self.input.clone()))
+                },
+            ],
+            None => vec![],
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(self.title.to_string())
+            .style(Style::default());
+        let widget = Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false });
+        return widget;
+    }
+
+    pub fn open(&mut self, cell_edit: CellEdit) {
+        self.is_null = cell_edit.target.value.is_null();
+        self.input = match cell_edit.target.value {
+            CellValue::Null => String::new(),
+            ref value => value.to_string(),
+        };
+        self.cell_edit = Some(cell_edit);
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.is_null = false;
+        self.input.push(c);
+    }
+
+    pub fn pop(&mut self) {
+        self.is_null = false;
+        self.input.pop();
+    }
+
+    pub fn toggle_null(&mut self) {
+        self.is_null = !self.is_null;
+        self.input.clear();
+    }
+
+    /// Width of the value shown on the input line, for placing the cursor after it.
+    pub fn input_width(&self) -> usize {
+        if self.is_null {
+            return 0;
+        }
+        return self.input.width();
+    }
+
+    /// Closes the editor, returning the edit and the value to assign, or None if the
+    /// value was left as it was.
+    pub fn take(&mut self) -> Option<(CellEdit, ColumnValue)> {
+        let cell_edit = self.cell_edit.take()?;
+        let value = if self.is_null {
+            CellValue::Null
+        } else {
+            CellValue::Text(self.input.clone())
+        };
+        let unchanged = match &cell_edit.target.value {
+            CellValue::Null => value.is_null(),
+            original => !value.is_null() && original.to_string() == self.input,
+        };
+        if unchanged {
+            return None;
+        }
+        let assignment = ColumnValue {
+            value: value,
+            ..cell_edit.target.clone()
+        };
+        return Some((cell_edit, assignment));
+    }
+
+    pub fn close(&mut self) {
+        self.cell_edit = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CellEdit, CellEditorWdg};
+    use crate::{db::statement::ColumnValue, model::cell::CellValue};
+
+    fn editor(value: CellValue) -> CellEditorWdg<'static> {
+        let mut editor = CellEditorWdg::new();
+        editor.open(CellEdit {
+            table: String::from("users"),
+            target: ColumnValue {
+                column: String::from("name"),
+                column_type: String::from("text"),
+                value: value,
+            },
+            primary_key: vec![],
+        });
+        return editor;
+    }
+
+    #[test]
+    fn unchanged_values_are_not_assigned() {
+        assert!(editor(CellValue::Null).take().is_none());
+        assert!(editor(CellValue::Text(String::from("a"))).take().is_none());
+    }
+
+    #[test]
+    fn toggles_null() {
+        let mut to_null = editor(CellValue::Text(String::from("a")));
+        to_null.toggle_null();
+        let (_, assignment) = to_null.take().unwrap();
+        assert!(assignment.value.is_null());
+
+        let mut from_null = editor(CellValue::Null);
+        from_null.push('b');
+        let (_, assignment) = from_null.take().unwrap();
+        assert_eq!(assignment.value.to_string(), "b");
+    }
+}
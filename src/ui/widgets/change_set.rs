@@ -0,0 +1,90 @@
+use tui::{
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, List, ListItem, ListState},
+};
+
+use crate::model::change_set::{ChangeSet, PendingChange};
+
+/// Review panel listing the exact SQL of every staged change.
+pub struct ChangeSetWdg<'a> {
+    title: &'a str,
+    pub change_set: ChangeSet,
+    pub select_state: ListState,
+}
+
+impl<'a> ChangeSetWdg<'a> {
+    pub fn new() -> Self {
+        Self {
+            title: "Pending changes [c: commit] [r: roll back all] [x: drop selected] [esc: close]",
+            change_set: ChangeSet::new(),
+            select_state: ListState::default(),
+        }
+    }
+
+    pub fn widget(&self) -> List<'a> {
+        let items: Vec<ListItem> = self
+            .change_set
+            .changes
+            .iter()
+            .map(|change| {
+                let color = match change {
+                    PendingChange::Update { .. } => Color::Yellow,
+                    PendingChange::Insert { .. } => Color::Green,
+                    PendingChange::Delete { .. } => Color::Red,
+                };
+                let lines: Vec<Spans> = change
+                    .statement()
+                    .preview()
+                    .lines()
+                    .map(|line| {
+                        Spans::from(Span::styled(line.to_string(), Style::default().fg(color)))
+                    })
+                    .collect();
+                ListItem::new(lines)
+            })
+            .collect();
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!("{} {}", self.change_set.table, self.title));
+        let widget = List::new(items)
+            .block(block)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        return widget;
+    }
+
+    pub fn open(&mut self) {
+        self.select_state.select(if self.change_set.is_empty() {
+            None
+        } else {
+            Some(0)
+        });
+    }
+
+    pub fn move_up(&mut self) {
+        if let Some(selected) = self.select_state.selected() {
+            self.select_state.select(Some(selected.saturating_sub(1)));
+        }
+    }
+
+    pub fn move_down(&mut self) {
+        if let Some(selected) = self.select_state.selected() {
+            if selected + 1 < self.change_set.changes.len() {
+                self.select_state.select(Some(selected + 1));
+            }
+        }
+    }
+
+    pub fn remove_selected(&mut self) {
+        if let Some(selected) = self.select_state.selected() {
+            self.change_set.remove(selected);
+        }
+        let len = self.change_set.changes.len();
+        self.select_state
+            .select(match self.select_state.selected() {
+                _ if len == 0 => None,
+                Some(selected) => Some(selected.min(len - 1)),
+                None => Some(0),
+            });
+    }
+}
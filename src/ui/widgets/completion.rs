@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+
+use tui::{
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, List, ListItem, ListState},
+};
+
+use crate::db::lexer::KEYWORDS;
+
+/// Where a completion comes from, shown next to it in the popup.
+#[derive(Clone, Copy, PartialEq)]
+pub enum CompletionKind {
+    Column,
+    Table,
+    Database,
+    Keyword,
+}
+
+impl CompletionKind {
+    fn label(&self) -> &'static str {
+        match self {
+            CompletionKind::Column => "column",
+            CompletionKind::Table => "table",
+            CompletionKind::Database => "database",
+            CompletionKind::Keyword => "keyword",
+        }
+    }
+}
+
+/// Completion popup for the word being typed in the SQL editor.
+pub struct CompletionWdg<'a> {
+    title: &'a str,
+    candidates: Vec<(String, CompletionKind)>,
+    pub select_state: ListState,
+    /// Column names of the tables seen in FROM/JOIN clauses so far, by table.
+    pub column_cache: HashMap<String, Vec<String>>,
+}
+
+impl<'a> CompletionWdg<'a> {
+    pub fn new() -> Self {
+        Self {
+            title: "tab: accept",
+            candidates: vec![],
+            select_state: ListState::default(),
+            column_cache: HashMap::new(),
+        }
+    }
+
+    pub fn widget(&self) -> List<'a> {
+        let items: Vec<ListItem> = self
+            .candidates
+            .iter()
+            .map(|(text, kind)| {
+                ListItem::new(Spans::from(vec![
+                    Span::from(format!("{:width$} ", text, width = self.text_width())),
+                    Span::styled(kind.label(), Style::default().fg(Color::DarkGray)),
+                ]))
+            })
+            .collect();
+        let widget = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(self.title.to_string()),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        return widget;
+    }
+
+    /// Width and height of the popup including its borders.
+    pub fn size(&self) -> (u16, u16) {
+        let label_width = self
+            .candidates
+            .iter()
+            .map(|(_, kind)| kind.label().len())
+            .max()
+            .unwrap_or(0);
+        let width = (self.text_width() + 1 + label_width).max(self.title.len());
+        return (
+            (width + 2) as u16,
+            (self.candidates.len().min(8) + 2) as u16,
+        );
+    }
+
+    fn text_width(&self) -> usize {
+        return self
+            .candidates
+            .iter()
+            .map(|(text, _)| text.chars().count())
+            .max()
+            .unwrap_or(0);
+    }
+
+    pub fn is_visible(&self) -> bool {
+        return !self.candidates.is_empty();
+    }
+
+    /// Refills the popup with the names starting with `prefix`, ignoring case:
+    /// columns of `tables` first, then tables, databases and keywords.
+    /// After a `.` only columns make sense, so nothing else is offered.
+    pub fn update(
+        &mut self,
+        prefix: &str,
+        after_dot: bool,
+        tables: &[String],
+        table_names: &[String],
+        databases: &[String],
+    ) {
+        self.candidates.clear();
+        if prefix.is_empty() && !after_dot {
+            self.select_state.select(None);
+            return;
+        }
+
+        let mut names: Vec<(String, CompletionKind)> = tables
+            .iter()
+            .filter_map(|table| self.column_cache.get(table))
+            .flatten()
+            .map(|column| (column.clone(), CompletionKind::Column))
+            .collect();
+        if !after_dot {
+            names.extend(
+                table_names
+                    .iter()
+                    .map(|table| (table.clone(), CompletionKind::Table)),
+            );
+            names.extend(
+                databases
+                    .iter()
+                    .map(|database| (database.clone(), CompletionKind::Database)),
+            );
+            // Keywords follow the case the user is typing in.
+            let lowercase = prefix.chars().all(|c| !c.is_uppercase());
+            names.extend(KEYWORDS.iter().map(|keyword| {
+                let keyword = if lowercase {
+                    keyword.to_lowercase()
+                } else {
+                    keyword.to_string()
+                };
+                (keyword, CompletionKind::Keyword)
+            }));
+        }
+
+        let prefix = prefix.to_lowercase();
+        for (name, kind) in names {
+            let lower = name.to_lowercase();
+            if lower.starts_with(&prefix)
+                && lower != prefix
+                && !self.candidates.iter().any(|(text, _)| *text == name)
+            {
+                self.candidates.push((name, kind));
+            }
+        }
+        self.select_state.select(if self.candidates.is_empty() {
+            None
+        } else {
+            Some(0)
+        });
+    }
+
+    pub fn close(&mut self) {
+        self.candidates.clear();
+        self.select_state.select(None);
+    }
+
+    pub fn move_up(&mut self) {
+        if let Some(selected) = self.select_state.selected() {
+            self.select_state.select(Some(selected.saturating_sub(1)));
+        }
+    }
+
+    pub fn move_down(&mut self) {
+        if let Some(selected) = self.select_state.selected() {
+            if selected + 1 < self.candidates.len() {
+                self.select_state.select(Some(selected + 1));
+            }
+        }
+    }
+
+    pub fn selected(&self) -> Option<String> {
+        let selected = self.select_state.selected()?;
+        return self.candidates.get(selected).map(|(text, _)| text.clone());
+    }
+}
@@ -1,9 +1,26 @@
-use crate::model::database::DatabaseModel;
+use crate::{
+    config::ConnectionProfile,
+    model::{
+        database::DatabaseModel,
+        table::{TableColumnModel, TableRecordModel},
+    },
+};
 
 use super::{
+    cell_editor::CellEditorWdg,
+    change_set::ChangeSetWdg,
+    completion::CompletionWdg,
     database::DatabaseWdg,
+    export_prompt::ExportPromptWdg,
+    file_prompt::FilePromptWdg,
+    filter_input::FilterInputWdg,
+    history_search::HistorySearchWdg,
+    import::ImportWdg,
+    profile_picker::ProfilePickerWdg,
+    row_form::RowFormWdg,
     sql_input::SqlInputWdg,
     sql_output::SqlOutputWdg,
+    status_bar::StatusBarWdg,
     tab::TabWdg,
     table::{table::TableWdg, table_column::TableColumnWdg, table_record::TableRecordWdg},
     table_list::TableListWdg,
@@ -12,27 +29,64 @@ use super::{
 pub struct WidgetCtx<'a> {
     pub database: DatabaseWdg<'a>,
     pub table_list: TableListWdg<'a>,
+    pub filter_input: FilterInputWdg<'a>,
     pub table: TableWdg<'a>,
     pub sql_input: SqlInputWdg<'a>,
     pub sql_output: SqlOutputWdg<'a>,
+    pub sql_result: Option<TableRecordWdg<'a>>,
     pub tab: TabWdg<'a>,
+    pub cell_editor: CellEditorWdg<'a>,
+    pub row_form: RowFormWdg<'a>,
+    pub change_set: ChangeSetWdg<'a>,
+    pub export_prompt: ExportPromptWdg<'a>,
+    pub import: ImportWdg<'a>,
+    pub history_search: HistorySearchWdg<'a>,
+    pub completion: CompletionWdg<'a>,
+    pub file_prompt: FilePromptWdg<'a>,
+    pub profile_picker: ProfilePickerWdg<'a>,
+    pub status_bar: StatusBarWdg,
 }
 
 impl<'a> WidgetCtx<'a> {
-    pub fn new(db_model: DatabaseModel) -> Self {
-        let default_table_name = db_model.current_table.name.clone();
-        let table_record_widget =
-            TableRecordWdg::new(default_table_name.clone(), db_model.current_table.record);
-        let table_column_widget =
-            TableColumnWdg::new(default_table_name.clone(), db_model.current_table.column);
+    /// The screen for `db_model`, reached through `profiles[active_profile]` if it came
+    /// from the config file.
+    pub fn new(
+        db_model: DatabaseModel,
+        profiles: Vec<ConnectionProfile>,
+        active_profile: Option<usize>,
+    ) -> Self {
+        // Empty until the first table is loaded
+        let table_record_widget = TableRecordWdg::new(String::new(), TableRecordModel::default());
+        let table_column_widget = TableColumnWdg::new(TableColumnModel::default());
+        let mut database = DatabaseWdg::new(
+            db_model.databases.clone(),
+            db_model.current_database.clone(),
+        );
+        database.profile = active_profile.and_then(|index| profiles.get(index).cloned());
+        let mut profile_picker = ProfilePickerWdg::new(profiles);
+        if active_profile.is_some() {
+            profile_picker.select_state.select(active_profile);
+        }
 
         Self {
-            database: DatabaseWdg::new(db_model.databases.clone()),
+            database: database,
             table_list: TableListWdg::new(db_model.tables.clone()),
+            filter_input: FilterInputWdg::new(),
             table: TableWdg::new(table_record_widget, table_column_widget),
             sql_input: SqlInputWdg::new(),
             sql_output: SqlOutputWdg::new(),
+            sql_result: None,
             tab: TabWdg::new(),
+            cell_editor: CellEditorWdg::new(),
+            row_form: RowFormWdg::new(),
+            change_set: ChangeSetWdg::new(),
+            export_prompt: ExportPromptWdg::new(),
+            import: ImportWdg::new(),
+            history_search: HistorySearchWdg::new(),
+            completion: CompletionWdg::new(),
+            file_prompt: FilePromptWdg::new(),
+            profile_picker: profile_picker,
+            status_bar: StatusBarWdg::new(),
         }
     }
 }
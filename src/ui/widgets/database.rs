@@ -4,30 +4,49 @@ use tui::{
     widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
 };
 
+use crate::config::ConnectionProfile;
+
+use super::profile_picker::profile_tag;
+
 pub struct DatabaseWdg<'a> {
     title: &'a str,
-    databases: Vec<String>,
+    pub databases: Vec<String>,
     pub current_database: String,
     pub database_select_state: ListState,
+    /// The config file's connection this is, tagged next to the title.
+    pub profile: Option<ConnectionProfile>,
 }
 
 impl<'a> DatabaseWdg<'a> {
-    pub fn new(databases: Vec<String>) -> Self {
+    pub fn new(databases: Vec<String>, current_database: String) -> Self {
         let mut database_select_state = ListState::default();
-        database_select_state.select(Some(0));
-        let current_database = databases[0].clone();
+        database_select_state.select(databases.iter().position(|db| *db == current_database));
 
         Self {
             title: "DB",
             databases: databases,
             current_database: current_database,
             database_select_state: database_select_state,
+            profile: None,
         }
     }
 
+    /// Whether the connection is a profile marked `read_only`, whose rows are not changed here.
+    pub fn is_read_only(&self) -> bool {
+        return self
+            .profile
+            .as_ref()
+            .is_some_and(|profile| profile.read_only);
+    }
+
     pub fn current_database_widget(&self) -> Paragraph<'a> {
+        let mut title = vec![Span::from(self.title)];
+        if let Some(profile) = self.profile.as_ref() {
+            title.push(Span::from(" "));
+            title.extend(profile_tag(profile));
+        }
         let block = Block::default()
-            .title(self.title.to_string())
+            .title(Spans::from(title))
             .borders(Borders::ALL);
         let widget = Paragraph::new(self.current_database.to_string())
             .style(Style::default())
@@ -79,9 +98,12 @@ impl<'a> DatabaseWdg<'a> {
         return selected < self.databases.len().saturating_sub(1);
     }
 
-    pub fn change_database(&mut self) {
-        if let Some(selected) = self.database_select_state.selected() {
-            self.current_database = self.databases[selected].clone();
-        }
+    pub fn selected_database(&self) -> Option<String> {
+        let selected = self.database_select_state.selected()?;
+        return self.databases.get(selected).cloned();
+    }
+
+    pub fn change_database(&mut self, database: String) {
+        self.current_database = database;
     }
 }
@@ -0,0 +1,102 @@
+use tui::{
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::export::ExportFormat;
+
+/// What an export writes: the records of the current table or the last EditSQL result.
+#[derive(Clone, Copy, PartialEq)]
+pub enum ExportSource {
+    Table,
+    QueryResult,
+}
+
+pub struct ExportPromptWdg<'a> {
+    title: &'a str,
+    path_label: &'a str,
+    pub input: String,
+    pub format: ExportFormat,
+    pub source: ExportSource,
+    /// Set once the user was told the path exists, so the next Enter replaces the file.
+    pub overwrite: bool,
+}
+
+impl<'a> ExportPromptWdg<'a> {
+    pub fn new() -> Self {
+        Self {
+            title: "Export [tab: format] [enter: write file] [esc: cancel]",
+            path_label: "Path: ",
+            input: String::new(),
+            format: ExportFormat::Csv,
+            source: ExportSource::Table,
+            overwrite: false,
+        }
+    }
+
+    pub fn widget(&self) -> Paragraph<'a> {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(self.title.to_string())
+            .style(Style::default());
+        let mut lines = vec![
+            Spans::from(vec![
+                Span::styled("Format: ", Style::default().add_modifier(Modifier::DIM)),
+                Span::from(self.format.to_string()),
+            ]),
+            Spans::from(vec![
+                Span::styled(
+                    self.path_label,
+                    Style::default().add_modifier(Modifier::DIM),
+                ),
+                Span::from(self.input.clone()),
+            ]),
+        ];
+        if self.overwrite {
+            lines.push(Spans::from(Span::styled(
+                "The file exists, enter again to overwrite it",
+                Style::default().fg(Color::Red),
+            )));
+        }
+        let widget = Paragraph::new(lines).block(block);
+        return widget;
+    }
+
+    /// Width of the text in front of the path input, for placing the cursor.
+    pub fn path_offset(&self) -> usize {
+        return self.path_label.len();
+    }
+
+    /// Opens the prompt with a default path named after `name` in the current format.
+    pub fn open(&mut self, source: ExportSource, name: &str) {
+        self.source = source;
+        self.input = format!("{}.{}", name, self.format.extension());
+        self.overwrite = false;
+    }
+
+    /// Shows the prompt again for a path that turned out to exist, to confirm replacing it.
+    pub fn ask_overwrite(&mut self) {
+        self.overwrite = true;
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.input.push(c);
+        self.overwrite = false;
+    }
+
+    pub fn pop(&mut self) {
+        self.input.pop();
+        self.overwrite = false;
+    }
+
+    /// Switches to the next format, keeping the path's extension in step with it.
+    pub fn cycle_format(&mut self) {
+        let extension = format!(".{}", self.format.extension());
+        self.format = self.format.next();
+        if let Some(stem) = self.input.strip_suffix(&extension) {
+            self.input = format!("{}.{}", stem, self.format.extension());
+            self.overwrite = false;
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use tui::{
+    style::{Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+};
+
+/// What the path typed into the file prompt is used for.
+#[derive(Clone, Copy, PartialEq)]
+pub enum FileAction {
+    Open,
+    Save,
+}
+
+pub struct FilePromptWdg<'a> {
+    open_title: &'a str,
+    save_title: &'a str,
+    path_label: &'a str,
+    pub input: String,
+    pub action: FileAction,
+    /// Set when opening a file would throw away unsaved changes in the editor.
+    discards_changes: bool,
+}
+
+impl<'a> FilePromptWdg<'a> {
+    pub fn new() -> Self {
+        Self {
+            open_title: "Open SQL file [enter: open] [esc: cancel]",
+            save_title: "Save SQL file [enter: save] [esc: cancel]",
+            path_label: "Path: ",
+            input: String::new(),
+            action: FileAction::Open,
+            discards_changes: false,
+        }
+    }
+
+    pub fn widget(&self) -> Paragraph<'a> {
+        let title = match self.action {
+            FileAction::Open => self.open_title,
+            FileAction::Save => self.save_title,
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title.to_string())
+            .style(Style::default());
+        let mut lines = vec![Spans::from(vec![
+            Span::styled(
+                self.path_label,
+                Style::default().add_modifier(Modifier::DIM),
+            ),
+            Span::from(self.input.clone()),
+        ])];
+        if self.discards_changes {
+            lines.push(Spans::from(Span::styled(
+                "Unsaved changes in the editor will be lost",
+                Style::default().add_modifier(Modifier::DIM),
+            )));
+        }
+        let widget = Paragraph::new(lines).block(block);
+        return widget;
+    }
+
+    /// Width of the text in front of the path input, for placing the cursor.
+    pub fn path_offset(&self) -> usize {
+        return self.path_label.len();
+    }
+
+    /// Opens the prompt for `action`, starting from the file the editor is tracking.
+    pub fn open(&mut self, action: FileAction, path: Option<&String>, discards_changes: bool) {
+        self.action = action;
+        self.input = path.cloned().unwrap_or_default();
+        self.discards_changes = discards_changes;
+    }
+}
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+use tui::{
+    style::Style,
+    widgets::{Block, Borders, Paragraph},
+};
+
+pub struct FilterInputWdg<'a> {
+    title: &'a str,
+    pub input: String,
+    filters: HashMap<String, String>,
+}
+
+impl<'a> FilterInputWdg<'a> {
+    pub fn new() -> Self {
+        Self {
+            title: "WHERE [f: edit filter] [enter: apply] [esc: cancel]",
+            input: String::new(),
+            filters: HashMap::new(),
+        }
+    }
+
+    pub fn widget(&self) -> Paragraph<'a> {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(self.title.to_string())
+            .style(Style::default());
+        let widget = Paragraph::new(self.input.clone())
+            .style(Style::default())
+            .block(block);
+        return widget;
+    }
+
+    /// Returns the filter remembered for `table` in this session.
+    pub fn filter_for(&self, table: &str) -> Option<String> {
+        return self.filters.get(table).cloned();
+    }
+
+    pub fn remember(&mut self, table: String, filter: Option<String>) {
+        match filter {
+            Some(filter) => self.filters.insert(table, filter),
+            None => self.filters.remove(&table),
+        };
+    }
+
+    /// Shows the filter remembered for `table`, discarding any unapplied edits.
+    pub fn load(&mut self, table: &str) {
+        self.input = self.filter_for(table).unwrap_or_default();
+    }
+
+    pub fn applied_input(&self) -> Option<String> {
+        if self.input.trim().is_empty() {
+            return None;
+        }
+        return Some(self.input.trim().to_string());
+    }
+}
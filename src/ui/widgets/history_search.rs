@@ -0,0 +1,138 @@
+use tui::{
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+};
+
+use crate::{db::lexer::Dialect, history::QueryHistory, ui::highlight::highlight};
+
+/// Incremental search over the query history, newest match first.
+pub struct HistorySearchWdg<'a> {
+    title: &'a str,
+    query_label: &'a str,
+    pub history: QueryHistory,
+    pub query: String,
+    pub select_state: ListState,
+    /// How the connected database reads SQL, for highlighting.
+    pub dialect: Dialect,
+}
+
+impl<'a> HistorySearchWdg<'a> {
+    pub fn new() -> Self {
+        Self {
+            title: "History [ctrl+r/down: older] [up: newer] [enter: use query] [esc: cancel]",
+            query_label: "Search: ",
+            history: QueryHistory::load(),
+            query: String::new(),
+            select_state: ListState::default(),
+            dialect: Dialect::default(),
+        }
+    }
+
+    pub fn query_widget(&self) -> Paragraph<'a> {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(self.title.to_string());
+        let widget = Paragraph::new(Spans::from(vec![
+            Span::styled(
+                self.query_label,
+                Style::default().add_modifier(Modifier::DIM),
+            ),
+            Span::from(self.query.clone()),
+        ]))
+        .block(block);
+        return widget;
+    }
+
+    pub fn list_widget(&self) -> List<'a> {
+        let matches = self.history.search(&self.query);
+        let items: Vec<ListItem> = matches
+            .iter()
+            .map(|entry| {
+                let status = if entry.success {
+                    Span::styled("ok  ", Style::default().fg(Color::Green))
+                } else {
+                    Span::styled("err ", Style::default().fg(Color::Red))
+                };
+                let sql = entry
+                    .sql
+                    .split_whitespace()
+                    .collect::<Vec<&str>>()
+                    .join(" ");
+                let mut spans = vec![
+                    Span::styled(
+                        format!(
+                            "{} {} {}ms ",
+                            entry.timestamp.format("%Y-%m-%d %H:%M"),
+                            entry.database,
+                            entry.duration_ms
+                        ),
+                        Style::default().add_modifier(Modifier::DIM),
+                    ),
+                    status,
+                ];
+                spans.extend(highlight(&sql, self.dialect).remove(0).0);
+                ListItem::new(Spans::from(spans))
+            })
+            .collect();
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!("{} matches", matches.len()));
+        let widget = List::new(items)
+            .block(block)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        return widget;
+    }
+
+    /// Width of the text in front of the search input, for placing the cursor.
+    pub fn query_offset(&self) -> usize {
+        return self.query_label.len();
+    }
+
+    pub fn open(&mut self) {
+        self.query.clear();
+        self.select_first();
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.query.push(c);
+        self.select_first();
+    }
+
+    pub fn pop(&mut self) {
+        self.query.pop();
+        self.select_first();
+    }
+
+    pub fn move_up(&mut self) {
+        if let Some(selected) = self.select_state.selected() {
+            self.select_state.select(Some(selected.saturating_sub(1)));
+        }
+    }
+
+    pub fn move_down(&mut self) {
+        if let Some(selected) = self.select_state.selected() {
+            if selected + 1 < self.history.search(&self.query).len() {
+                self.select_state.select(Some(selected + 1));
+            }
+        }
+    }
+
+    pub fn selected_sql(&self) -> Option<String> {
+        let selected = self.select_state.selected()?;
+        return self
+            .history
+            .search(&self.query)
+            .get(selected)
+            .map(|entry| entry.sql.clone());
+    }
+
+    fn select_first(&mut self) {
+        self.select_state
+            .select(if self.history.search(&self.query).is_empty() {
+                None
+            } else {
+                Some(0)
+            });
+    }
+}
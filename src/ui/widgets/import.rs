@@ -0,0 +1,165 @@
+use tui::{
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::import::{ImportPlan, RowError};
+
+pub enum ImportPhase {
+    Path,
+    Preview,
+    Report,
+}
+
+/// Walks a CSV import through choosing the file, previewing the column mapping
+/// and reviewing rows that failed before committing.
+pub struct ImportWdg<'a> {
+    path_label: &'a str,
+    pub input: String,
+    pub phase: ImportPhase,
+    pub plan: Option<ImportPlan>,
+    pub errors: Vec<RowError>,
+    pub imported: usize,
+    scroll: u16,
+}
+
+impl<'a> ImportWdg<'a> {
+    pub fn new() -> Self {
+        Self {
+            path_label: "CSV file: ",
+            input: String::new(),
+            phase: ImportPhase::Path,
+            plan: None,
+            errors: vec![],
+            imported: 0,
+            scroll: 0,
+        }
+    }
+
+    pub fn widget(&self) -> Paragraph<'a> {
+        let dim = Style::default().add_modifier(Modifier::DIM);
+        let (title, mut lines) = match (&self.phase, &self.plan) {
+            (ImportPhase::Preview, Some(plan)) => (
+                "Import preview [enter: import] [up/down: scroll] [esc: cancel]",
+                self.preview_lines(plan),
+            ),
+            (ImportPhase::Report, Some(plan)) => (
+                "Import failed for some rows [c: commit imported rows] [r: roll back all]",
+                vec![Spans::from(format!(
+                    "{} of {} rows imported into {}, {} failed",
+                    self.imported,
+                    plan.rows.len(),
+                    plan.table,
+                    self.errors.len()
+                ))],
+            ),
+            _ => (
+                "Import CSV [enter: preview] [esc: cancel]",
+                vec![Spans::from(vec![
+                    Span::styled(self.path_label, dim),
+                    Span::from(self.input.clone()),
+                ])],
+            ),
+        };
+        if !matches!(self.phase, ImportPhase::Path) {
+            lines.extend(self.errors.iter().map(|error| {
+                Spans::from(Span::styled(
+                    format!("row {}: {}", error.row, error.message),
+                    Style::default().fg(Color::Red),
+                ))
+            }));
+        }
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .style(Style::default());
+        let widget = Paragraph::new(lines).block(block).scroll((self.scroll, 0));
+        return widget;
+    }
+
+    fn preview_lines(&self, plan: &ImportPlan) -> Vec<Spans<'a>> {
+        let dim = Style::default().add_modifier(Modifier::DIM);
+        let mut lines = vec![
+            Spans::from(format!(
+                "{}: {} rows into {}",
+                plan.path,
+                plan.rows.len(),
+                plan.table
+            )),
+            Spans::from(""),
+        ];
+        for (column, index) in plan.mapping.iter() {
+            let source = match index {
+                Some(index) => Span::from(format!("<- {}", plan.headers[*index])),
+                None => Span::styled("<- not in CSV, keeps its default", dim),
+            };
+            lines.push(Spans::from(vec![
+                Span::from(format!("{} ({}) ", column.name, column.column_type)),
+                source,
+            ]));
+        }
+        let unmapped = plan.unmapped_headers();
+        if !unmapped.is_empty() {
+            lines.push(Spans::from(Span::styled(
+                format!(
+                    "Ignored CSV columns: {}",
+                    unmapped
+                        .iter()
+                        .map(|header| header.as_str())
+                        .collect::<Vec<&str>>()
+                        .join(", ")
+                ),
+                dim,
+            )));
+        }
+        lines.push(Spans::from(""));
+        lines.push(Spans::from(if self.errors.is_empty() {
+            String::from("All rows pass the type checks")
+        } else {
+            format!(
+                "{} rows fail the type checks and will be skipped:",
+                self.errors.len()
+            )
+        }));
+        return lines;
+    }
+
+    /// Width of the text in front of the path input, for placing the cursor.
+    pub fn path_offset(&self) -> usize {
+        return self.path_label.len();
+    }
+
+    pub fn open(&mut self) {
+        self.input.clear();
+        self.phase = ImportPhase::Path;
+        self.plan = None;
+        self.errors.clear();
+        self.imported = 0;
+        self.scroll = 0;
+    }
+
+    pub fn preview(&mut self, plan: ImportPlan) {
+        self.errors = plan.type_errors();
+        self.plan = Some(plan);
+        self.phase = ImportPhase::Preview;
+        self.scroll = 0;
+    }
+
+    pub fn report(&mut self, plan: ImportPlan, imported: usize, errors: Vec<RowError>) {
+        self.plan = Some(plan);
+        self.imported = imported;
+        self.errors = errors;
+        self.phase = ImportPhase::Report;
+        self.scroll = 0;
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+
+    pub fn scroll_down(&mut self) {
+        self.scroll = self.scroll.saturating_add(1);
+    }
+}
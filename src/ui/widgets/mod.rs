@@ -1,7 +1,19 @@
+pub mod cell_editor;
+pub mod change_set;
+pub mod completion;
 pub mod ctx;
 pub mod database;
+pub mod export_prompt;
+pub mod file_prompt;
+pub mod filter_input;
+pub mod history_search;
+pub mod import;
+pub mod profile_picker;
+pub mod row_form;
 pub mod sql_input;
 pub mod sql_output;
+pub mod status_bar;
 pub mod tab;
 pub mod table;
 pub mod table_list;
+pub mod text_buffer;
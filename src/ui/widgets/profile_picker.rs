@@ -0,0 +1,213 @@
+use tui::{
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+};
+
+use crate::{config::ConnectionProfile, ui::spinner::spinner_frame};
+
+/// Where the connection picker is at.
+#[derive(Clone, Copy, PartialEq)]
+pub enum PickerPhase {
+    List,
+    Password,
+    Connecting,
+}
+
+/// The connection profiles of the config file, to pick one to connect to.
+pub struct ProfilePickerWdg<'a> {
+    title: &'a str,
+    password_label: &'a str,
+    pub profiles: Vec<ConnectionProfile>,
+    pub select_state: ListState,
+    pub phase: PickerPhase,
+    /// What was typed at the password prompt, only ever shown masked.
+    password: String,
+    error: Option<String>,
+    tick: usize,
+}
+
+impl<'a> ProfilePickerWdg<'a> {
+    pub fn new(profiles: Vec<ConnectionProfile>) -> Self {
+        let mut select_state = ListState::default();
+        if !profiles.is_empty() {
+            select_state.select(Some(0));
+        }
+
+        Self {
+            title: "Connections [enter: connect] [esc: cancel]",
+            password_label: "Password: ",
+            profiles: profiles,
+            select_state: select_state,
+            phase: PickerPhase::List,
+            password: String::new(),
+            error: None,
+            tick: 0,
+        }
+    }
+
+    pub fn list_widget(&self) -> List<'a> {
+        let items: Vec<ListItem> = self
+            .profiles
+            .iter()
+            .map(|profile| {
+                let mut spans = profile_tag(profile);
+                spans.push(Span::styled(
+                    format!(" {}", profile_target(profile)),
+                    Style::default().add_modifier(Modifier::DIM),
+                ));
+                ListItem::new(Spans::from(spans))
+            })
+            .collect();
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(self.title.to_string());
+        let widget = List::new(items)
+            .block(block)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        return widget;
+    }
+
+    /// The password prompt, the connection in progress or the last error, under the list.
+    pub fn status_widget(&self) -> Paragraph<'a> {
+        let line = match (self.phase, self.error.as_ref()) {
+            (PickerPhase::Password, _) => Spans::from(vec![
+                Span::styled(
+                    self.password_label,
+                    Style::default().add_modifier(Modifier::DIM),
+                ),
+                Span::from("*".repeat(self.password.chars().count())),
+            ]),
+            (PickerPhase::Connecting, _) => Spans::from(Span::from(format!(
+                "{} Connecting to {} [esc: cancel]",
+                spinner_frame(self.tick),
+                self.selected_name()
+            ))),
+            (PickerPhase::List, Some(error)) => {
+                Spans::from(Span::styled(error.clone(), Style::default().fg(Color::Red)))
+            }
+            (PickerPhase::List, None) => Spans::default(),
+        };
+        let block = Block::default().borders(Borders::ALL);
+        let widget = Paragraph::new(line).block(block);
+        return widget;
+    }
+
+    /// Width of the text in front of the masked password, for placing the cursor.
+    pub fn password_offset(&self) -> usize {
+        return self.password_label.len() + self.password.chars().count();
+    }
+
+    pub fn open(&mut self) {
+        self.phase = PickerPhase::List;
+        self.password.clear();
+        self.error = None;
+    }
+
+    pub fn move_up(&mut self) {
+        if let Some(selected) = self.select_state.selected() {
+            self.select_state.select(Some(selected.saturating_sub(1)));
+        }
+    }
+
+    pub fn move_down(&mut self) {
+        if let Some(selected) = self.select_state.selected() {
+            if selected + 1 < self.profiles.len() {
+                self.select_state.select(Some(selected + 1));
+            }
+        }
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        return self.select_state.selected();
+    }
+
+    fn selected_name(&self) -> String {
+        return self
+            .selected()
+            .and_then(|index| self.profiles.get(index))
+            .map(|profile| profile.name.clone())
+            .unwrap_or_default();
+    }
+
+    pub fn ask_password(&mut self) {
+        self.password.clear();
+        self.error = None;
+        self.phase = PickerPhase::Password;
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.password.push(c);
+    }
+
+    pub fn pop(&mut self) {
+        self.password.pop();
+    }
+
+    /// The typed password, cleared from the prompt.
+    pub fn take_password(&mut self) -> String {
+        return std::mem::take(&mut self.password);
+    }
+
+    pub fn start_connecting(&mut self) {
+        self.error = None;
+        self.phase = PickerPhase::Connecting;
+    }
+
+    /// Back to the list, showing why connecting did not work.
+    pub fn set_error(&mut self, error: String) {
+        self.password.clear();
+        self.error = Some(error);
+        self.phase = PickerPhase::List;
+    }
+
+    pub fn cancel_connecting(&mut self) {
+        self.phase = PickerPhase::List;
+    }
+
+    pub fn tick(&mut self) {
+        self.tick += 1;
+    }
+}
+
+/// The profile's name in its colour, followed by a read-only marker if it has one.
+pub fn profile_tag(profile: &ConnectionProfile) -> Vec<Span<'static>> {
+    let color = match profile.color.as_deref().map(str::to_lowercase).as_deref() {
+        Some("red") => Color::Red,
+        Some("green") => Color::Green,
+        Some("yellow") => Color::Yellow,
+        Some("blue") => Color::Blue,
+        Some("magenta") => Color::Magenta,
+        Some("cyan") => Color::Cyan,
+        Some("gray") | Some("grey") => Color::Gray,
+        Some("white") => Color::White,
+        _ => Color::Reset,
+    };
+    let style = if color == Color::Reset {
+        Style::default().add_modifier(Modifier::BOLD)
+    } else {
+        Style::default()
+            .fg(Color::Black)
+            .bg(color)
+            .add_modifier(Modifier::BOLD)
+    };
+    let mut spans = vec![Span::styled(format!(" {} ", profile.name), style)];
+    if profile.read_only {
+        spans.push(Span::styled(
+            " read-only",
+            Style::default().add_modifier(Modifier::DIM),
+        ));
+    }
+    return spans;
+}
+
+/// Where the profile connects to, without asking for its password.
+fn profile_target(profile: &ConnectionProfile) -> String {
+    let target = profile
+        .url(None)
+        .unwrap_or_else(|_| profile.driver.clone().unwrap_or_default());
+    return match profile.database.as_ref() {
+        Some(database) => format!("{} ({})", target, database),
+        None => target,
+    };
+}
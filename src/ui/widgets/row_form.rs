@@ -0,0 +1,190 @@
+use tui::{
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    db::statement::ColumnValue,
+    model::{cell::CellValue, table::ColumnDefinition},
+};
+
+struct RowFormField {
+    definition: ColumnDefinition,
+    input: String,
+    is_null: bool,
+}
+
+impl RowFormField {
+    fn label(&self) -> String {
+        let nullability = if self.definition.nullable {
+            "NULL"
+        } else {
+            "NOT NULL"
+        };
+        return match &self.definition.default {
+            Some(default) => format!(
+                "{} {} {} DEFAULT {}",
+                self.definition.name, self.definition.column_type, nullability, default
+            ),
+            None => format!(
+                "{} {} {}",
+                self.definition.name, self.definition.column_type, nullability
+            ),
+        };
+    }
+}
+
+/// A form with one input per column for inserting a new record.
+/// Empty inputs are left out of the INSERT so the column default applies.
+pub struct RowFormWdg<'a> {
+    title: &'a str,
+    table: String,
+    fields: Vec<RowFormField>,
+    selected: usize,
+}
+
+impl<'a> RowFormWdg<'a> {
+    pub fn new() -> Self {
+        Self {
+            title:
+                "New row [tab/up/down: field] [ctrl-n: NULL] [enter: stage INSERT] [esc: cancel]",
+            table: String::new(),
+            fields: vec![],
+            selected: 0,
+        }
+    }
+
+    /// Lines scrolled off the top so the selected field stays inside `height` lines.
+    fn scroll_offset(&self, height: u16) -> u16 {
+        return (self.selected as u16).saturating_sub(height.saturating_sub(1));
+    }
+
+    fn label_width(&self) -> usize {
+        return self
+            .fields
+            .iter()
+            .map(|field| field.label().width())
+            .max()
+            .unwrap_or(0);
+    }
+
+    pub fn widget(&self, height: u16) -> Paragraph<'a> {
+        let label_width = self.label_width();
+        let lines: Vec<Spans> = self
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| {
+                let marker = if index == self.selected { "> " } else { "  " };
+                let label = format!(
+                    "{}{:<width$} = ",
+                    marker,
+                    field.label(),
+                    width = label_width
+                );
+                let value = if field.is_null {
+                    Span::styled("NULL", Style::default().fg(Color::DarkGray))
+                } else {
+                    Span::from(field.input.clone())
+                };
+                let label_style = if index == self.selected {
+                    Style::default().add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().add_modifier(Modifier::DIM)
+                };
+                Spans::from(vec![Span::styled(label, label_style), value])
+            })
+            .collect();
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!("{} {}", self.table, self.title));
+        return Paragraph::new(lines)
+            .block(block)
+            .scroll((self.scroll_offset(height.saturating_sub(2)), 0));
+    }
+
+    /// Cursor position inside the form's area, past the end of the selected input.
+    pub fn cursor_position(&self, height: u16) -> (u16, u16) {
+        let input_width = match self.fields.get(self.selected) {
+            Some(field) if !field.is_null => field.input.width(),
+            _ => 0,
+        };
+        // Two columns for the marker and three for " = "
+        let x = 2 + self.label_width() + 3 + input_width;
+        let y = self.selected as u16 - self.scroll_offset(height.saturating_sub(2));
+        return (x as u16 + 1, y + 1);
+    }
+
+    pub fn open(&mut self, table: String, definitions: Vec<ColumnDefinition>) {
+        self.table = table;
+        self.fields = definitions
+            .into_iter()
+            .map(|definition| RowFormField {
+                definition: definition,
+                input: String::new(),
+                is_null: false,
+            })
+            .collect();
+        self.selected = 0;
+    }
+
+    pub fn close(&mut self) {
+        self.fields.clear();
+    }
+
+    pub fn move_up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn move_down(&mut self) {
+        if self.selected + 1 < self.fields.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn push(&mut self, c: char) {
+        if let Some(field) = self.fields.get_mut(self.selected) {
+            field.is_null = false;
+            field.input.push(c);
+        }
+    }
+
+    pub fn pop(&mut self) {
+        if let Some(field) = self.fields.get_mut(self.selected) {
+            field.is_null = false;
+            field.input.pop();
+        }
+    }
+
+    pub fn toggle_null(&mut self) {
+        if let Some(field) = self.fields.get_mut(self.selected) {
+            field.is_null = !field.is_null;
+            field.input.clear();
+        }
+    }
+
+    /// The filled in fields, closing the form. Fails while every field is empty.
+    pub fn take_values(&mut self) -> Result<Vec<ColumnValue>, String> {
+        let values: Vec<ColumnValue> = self
+            .fields
+            .iter()
+            .filter(|field| field.is_null || !field.input.is_empty())
+            .map(|field| ColumnValue {
+                column: field.definition.name.clone(),
+                column_type: field.definition.column_type.clone(),
+                value: if field.is_null {
+                    CellValue::Null
+                } else {
+                    CellValue::Text(field.input.clone())
+                },
+            })
+            .collect();
+        if values.is_empty() {
+            return Err(String::from("Fill in at least one column to insert a row"));
+        }
+        self.close();
+        Ok(values)
+    }
+}
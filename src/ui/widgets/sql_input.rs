@@ -1,29 +1,147 @@
+use std::{fs, io, path::Path};
+
 use tui::{
+    layout::Rect,
     style::Style,
+    text::{Span, Spans},
     widgets::{Block, Borders, Paragraph},
 };
 
+use crate::{db::lexer::Dialect, ui::highlight::char_styles};
+
+use super::text_buffer::TextBuffer;
+
 pub struct SqlInputWdg<'a> {
     title: &'a str,
-    pub input: String,
+    pub buffer: TextBuffer,
+    /// Whether a script keeps going after a statement fails.
+    pub continue_on_error: bool,
+    /// The `.sql` file the editor was opened from or last saved to.
+    pub path: Option<String>,
+    saved_text: String,
+    /// How the connected database reads SQL, for highlighting.
+    pub dialect: Dialect,
 }
 
 impl<'a> SqlInputWdg<'a> {
     pub fn new() -> Self {
         Self {
-            title: "SQL [e: start editing] [esc: stop editing]",
-            input: String::new(),
+            title: "[e: edit] [alt+enter/F5: execute] [ctrl+o/s/e: open/save/$EDITOR]",
+            buffer: TextBuffer::new(),
+            continue_on_error: false,
+            path: None,
+            saved_text: String::new(),
+            dialect: Dialect::default(),
         }
     }
 
-    pub fn widget(&self) -> Paragraph<'a> {
+    /// The highlighted editor for `area`, wrapped to its width and scrolled so the cursor
+    /// stays visible.
+    pub fn widget(&self, area: Rect) -> Paragraph<'a> {
+        let (rows, (_, cursor_y)) = self.buffer.wrapped(area.width.saturating_sub(2) as usize);
+        let text = self.buffer.text();
+        // The wrapped rows hold every character but the newlines, in order.
+        let mut styles = text
+            .chars()
+            .zip(char_styles(&text, self.dialect))
+            .filter(|(c, _)| *c != '\n')
+            .map(|(_, style)| style);
+        let lines: Vec<Spans> = rows
+            .into_iter()
+            .map(|row| {
+                let mut spans: Vec<Span> = vec![];
+                for c in row.chars() {
+                    let style = styles.next().unwrap_or_default();
+                    match spans.last_mut() {
+                        Some(span) if span.style == style => span.content.to_mut().push(c),
+                        _ => spans.push(Span::styled(c.to_string(), style)),
+                    }
+                }
+                Spans::from(spans)
+            })
+            .collect();
+
         let block = Block::default()
             .borders(Borders::ALL)
-            .title(self.title.to_string())
+            .title(format!(
+                "SQL{} {} [ctrl+t: on error {}] [esc: stop editing]",
+                self.file_label(),
+                self.title,
+                if self.continue_on_error {
+                    "continue"
+                } else {
+                    "stop"
+                }
+            ))
             .style(Style::default());
-        let widget = Paragraph::new(self.input.clone())
+        let widget = Paragraph::new(lines)
             .style(Style::default())
-            .block(block);
+            .block(block)
+            .scroll((scroll_offset(cursor_y, area), 0));
         return widget;
     }
+
+    /// Where the terminal cursor goes when the editor is drawn in `area`.
+    pub fn cursor_position(&self, area: Rect) -> (u16, u16) {
+        let (_, (cursor_x, cursor_y)) = self.buffer.wrapped(area.width.saturating_sub(2) as usize);
+        return (
+            // Move past the left border
+            area.x + cursor_x as u16 + 1,
+            // Move one line down, from the border to the first text line
+            area.y + (cursor_y as u16 - scroll_offset(cursor_y, area)) + 1,
+        );
+    }
+
+    pub fn input(&self) -> String {
+        return self.buffer.text();
+    }
+
+    /// Whether the text differs from the file it was opened from or saved to.
+    pub fn is_modified(&self) -> bool {
+        return self.path.is_some() && self.buffer.text() != self.saved_text;
+    }
+
+    /// Whether replacing the text would lose anything, including text never saved to a file.
+    pub fn has_unsaved_text(&self) -> bool {
+        return match self.path {
+            Some(_) => self.is_modified(),
+            None => !self.buffer.text().is_empty(),
+        };
+    }
+
+    /// Replaces the editor's text with the contents of `path`.
+    pub fn open_file(&mut self, path: &str) -> io::Result<()> {
+        let text = fs::read_to_string(path)?;
+        self.buffer
+            .set_text(text.strip_suffix('\n').unwrap_or(&text));
+        self.path = Some(path.to_string());
+        self.saved_text = self.buffer.text();
+        return Ok(());
+    }
+
+    /// Writes the editor's text to `path`, which becomes the file the editor is tracking.
+    pub fn save_file(&mut self, path: &str) -> io::Result<()> {
+        let text = self.buffer.text();
+        fs::write(path, format!("{}\n", text))?;
+        self.path = Some(path.to_string());
+        self.saved_text = text;
+        return Ok(());
+    }
+
+    /// The file name for the title, with `*` when there are unsaved changes.
+    fn file_label(&self) -> String {
+        let name = match self.path.as_ref() {
+            Some(path) => Path::new(path)
+                .file_name()
+                .map_or(path.clone(), |name| name.to_string_lossy().to_string()),
+            None => return String::new(),
+        };
+        return format!(" {}{}", name, if self.is_modified() { "*" } else { "" });
+    }
+}
+
+/// Rows scrolled off the top so that row `cursor_y` is inside `area`'s borders.
+fn scroll_offset(cursor_y: usize, area: Rect) -> u16 {
+    let height = area.height.saturating_sub(2).max(1);
+    return (cursor_y as u16).saturating_sub(height - 1);
 }
@@ -1,6 +1,11 @@
-use sqlx::mysql::MySqlQueryResult;
+use std::time::Duration;
+
+use crate::{
+    db::sql_client::{QueryResult, StatementResult},
+    ui::spinner::spinner_frame,
+};
 use tui::{
-    style::Style,
+    style::{Color, Modifier, Style},
     text::{Span, Spans},
     widgets::{Block, Borders, Paragraph, Wrap},
 };
@@ -18,6 +23,11 @@ impl<'a> SqlOutputWdg<'a> {
         }
     }
 
+    /// Height that fits every line of output between the borders.
+    pub fn height(&self) -> u16 {
+        return (self.output.len() + 2) as u16;
+    }
+
     pub fn widget(&self) -> Paragraph<'a> {
         let block = Block::default()
             .borders(Borders::ALL)
@@ -37,19 +47,133 @@ impl<'a> SqlOutputWdg<'a> {
         ];
     }
 
-    pub fn set_success_msg(&mut self, query_result: MySqlQueryResult) {
+    pub fn set_success_msg(&mut self, rows_affected: u64, last_insert_id: Option<u64>) {
         self.output = vec![
             Spans::from(Span::from("Success to execute")),
             Spans::from(Span::from(format!(
                 "{} {}",
-                "rows_affected:",
-                query_result.rows_affected().to_string()
+                "rows_affected:", rows_affected
             ))),
-            Spans::from(Span::from(format!(
+        ];
+        if let Some(last_insert_id) = last_insert_id {
+            self.output.push(Spans::from(Span::from(format!(
                 "{} {}",
-                "last_insert_id:",
-                query_result.last_insert_id().to_string()
+                "last_insert_id:", last_insert_id
+            ))));
+        }
+    }
+
+    pub fn set_rows_msg(&mut self, row_count: usize) {
+        self.output = vec![
+            Spans::from(Span::from("Success to execute")),
+            Spans::from(Span::from(format!("{} {}", "rows_returned:", row_count))),
+        ];
+    }
+
+    pub fn set_export_msg(&mut self, row_count: usize, path: String) {
+        self.output = vec![
+            Spans::from(Span::from("Success to export")),
+            Spans::from(Span::from(format!(
+                "{} {} {} {}",
+                "rows_written:", row_count, "to", path
+            ))),
+        ];
+    }
+
+    pub fn set_progress_msg(&mut self, action: &str, processed: usize, total: usize) {
+        self.output = vec![
+            Spans::from(Span::from(format!("{}...", action))),
+            Spans::from(Span::from(format!(
+                "{} {} of {}",
+                "rows_processed:", processed, total
+            ))),
+        ];
+    }
+
+    /// Spinner shown while statement `index` of `total` runs, `tick` picking its frame.
+    pub fn set_running_msg(
+        &mut self,
+        tick: usize,
+        index: usize,
+        total: usize,
+        elapsed: Duration,
+        cancelling: bool,
+    ) {
+        let status = if cancelling {
+            "cancelling..."
+        } else {
+            "[ctrl+c: cancel]"
+        };
+        self.output = vec![
+            Spans::from(Span::from(format!(
+                "{} Running statement {} of {}",
+                spinner_frame(tick),
+                index + 1,
+                total
+            ))),
+            Spans::from(Span::from(format!(
+                "{} {:.1}s {}",
+                "elapsed:",
+                elapsed.as_secs_f64(),
+                status
             ))),
         ];
     }
+
+    /// Adds a red line under the current output, for notes about how it ended.
+    pub fn append_error_msg(&mut self, error: String) {
+        self.output.push(Spans::from(Span::styled(
+            error,
+            Style::default().fg(Color::Red),
+        )));
+    }
+
+    pub fn set_file_msg(&mut self, action: &str, path: &str) {
+        self.output = vec![
+            Spans::from(Span::from(format!("Success to {}", action))),
+            Spans::from(Span::from(format!("{} {}", "path:", path))),
+        ];
+    }
+
+    /// One line per statement of a script that ran `results.len()` of its `total` statements.
+    pub fn set_script_msg(&mut self, results: &[StatementResult], total: usize) {
+        let failed = results
+            .iter()
+            .filter(|result| result.result.is_err())
+            .count();
+        let mut summary = format!(
+            "{} {} of {}, {} {}",
+            "statements_run:",
+            results.len(),
+            total,
+            "failed:",
+            failed
+        );
+        if results.len() < total {
+            summary.push_str(" (stopped at the error)");
+        }
+        self.output = vec![Spans::from(Span::from(summary))];
+        for (index, result) in results.iter().enumerate() {
+            let outcome = match &result.result {
+                Ok(QueryResult::Rows(_, records)) => {
+                    Span::from(format!("{} {}", "rows_returned:", records.len()))
+                }
+                Ok(QueryResult::Summary { rows_affected, .. }) => {
+                    Span::from(format!("{} {}", "rows_affected:", rows_affected))
+                }
+                Err(e) => Span::styled(
+                    format!("{} {}", "error:", e),
+                    Style::default().fg(Color::Red),
+                ),
+            };
+            self.output.push(Spans::from(vec![
+                Span::from(format!("#{} {}ms ", index + 1, result.duration.as_millis())),
+                outcome,
+                Span::styled(
+                    format!("  {}", result.sql.lines().next().unwrap_or_default()),
+                    Style::default().add_modifier(Modifier::DIM),
+                ),
+            ]));
+        }
+    }
 }
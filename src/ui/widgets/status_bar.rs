@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+use tui::{
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::Paragraph,
+};
+
+/// One line under every layout for problems with the connection itself, as opposed to
+/// the output of what the user ran. Hidden while there is nothing to report.
+pub struct StatusBarWdg {
+    message: Option<Spans<'static>>,
+    /// Kept until the state it describes is over, instead of until the next key press.
+    sticky: bool,
+}
+
+impl StatusBarWdg {
+    pub fn new() -> Self {
+        Self {
+            message: None,
+            sticky: false,
+        }
+    }
+
+    pub fn widget(&self) -> Option<Paragraph<'static>> {
+        let message = self.message.clone()?;
+        return Some(Paragraph::new(message));
+    }
+
+    pub fn set_error_msg(&mut self, error: String) {
+        self.message = Some(Spans::from(vec![
+            Span::styled(
+                " error ",
+                Style::default()
+                    .fg(Color::White)
+                    .bg(Color::Red)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::from(format!(" {} [any key: dismiss]", error)),
+        ]));
+        self.sticky = false;
+    }
+
+    pub fn set_connection_lost_msg(&mut self, attempt: u32, error: &str, retry_in: Duration) {
+        self.message = Some(Spans::from(vec![Span::styled(
+            " disconnected ",
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )]));
+        let retry = if attempt == 0 {
+            format!(" {} (reconnecting in {}s)", error, retry_in.as_secs())
+        } else {
+            format!(
+                " {} (reconnect attempt {} failed, retrying in {}s)",
+                error,
+                attempt,
+                retry_in.as_secs()
+            )
+        };
+        if let Some(message) = self.message.as_mut() {
+            message.0.push(Span::from(retry));
+        }
+        self.sticky = true;
+    }
+
+    pub fn set_reconnected_msg(&mut self) {
+        self.message = Some(Spans::from(vec![
+            Span::styled(
+                " connected ",
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::from(" The connection is back [any key: dismiss]"),
+        ]));
+        self.sticky = false;
+    }
+
+    /// Clears the message unless it is sticky.
+    pub fn dismiss(&mut self) {
+        if !self.sticky {
+            self.message = None;
+        }
+    }
+}
@@ -1,10 +1,28 @@
-use crate::{model::table::TableModel, ui::widgets::tab::TableMode};
+use tui::{
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Cell, Paragraph},
+};
+
+use crate::{
+    db::statement::ColumnValue,
+    model::{cell::CellValue, table::TableModel},
+    ui::{
+        spinner::spinner_frame,
+        widgets::{cell_editor::CellEdit, tab::TableMode},
+    },
+};
 
 use super::{table_column::TableColumnWdg, table_record::TableRecordWdg};
 
 pub struct TableWdg<'a> {
     pub record_widget: TableRecordWdg<'a>,
     pub column_widget: TableColumnWdg<'a>,
+    /// What is being loaded in the background, shown instead of the grids meanwhile.
+    loading: Option<String>,
+    loading_cancellable: bool,
+    /// Set while changes are written, which nothing but quitting may interrupt.
+    writing: bool,
+    loading_tick: usize,
 }
 
 pub struct SelectableRange {
@@ -17,22 +35,120 @@ pub struct VisibleRange {
     pub end_column_index: usize,
 }
 
-pub trait TableWdgTrait {
-    fn move_right();
-    fn move_left();
-}
-
 impl<'a> TableWdg<'a> {
     pub fn new(record_widget: TableRecordWdg<'a>, column_widget: TableColumnWdg<'a>) -> Self {
         Self {
             record_widget: record_widget,
             column_widget: column_widget,
+            loading: None,
+            loading_cancellable: false,
+            writing: false,
+            loading_tick: 0,
         }
     }
 
+    pub fn start_loading(&mut self, what: String, cancellable: bool) {
+        self.loading = Some(what);
+        self.loading_cancellable = cancellable;
+        self.writing = false;
+        self.loading_tick = 0;
+    }
+
+    /// Like `start_loading` for a write described by `what`, e.g. `Committing 3 changes`.
+    pub fn start_writing(&mut self, what: String) {
+        self.start_loading(what, false);
+        self.writing = true;
+    }
+
+    pub fn finish_loading(&mut self) {
+        self.loading = None;
+    }
+
+    pub fn is_loading(&self) -> bool {
+        return self.loading.is_some();
+    }
+
+    pub fn is_writing(&self) -> bool {
+        return self.is_loading() && self.writing;
+    }
+
+    pub fn can_cancel_loading(&self) -> bool {
+        return self.is_loading() && self.loading_cancellable;
+    }
+
+    /// Moves the loading spinner on by one frame.
+    pub fn tick(&mut self) {
+        self.loading_tick += 1;
+    }
+
+    /// Placeholder for the grids while something is loading.
+    pub fn loading_widget(&self) -> Option<Paragraph<'a>> {
+        let what = self.loading.as_ref()?;
+        if self.writing {
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .title(String::from("Writing"));
+            let text = format!("{} {}...", spinner_frame(self.loading_tick), what);
+            return Some(Paragraph::new(text).block(block));
+        }
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(String::from("Loading"));
+        let mut text = format!("{} Loading {}...", spinner_frame(self.loading_tick), what);
+        if self.loading_cancellable {
+            text.push_str(" [esc: cancel]");
+        }
+        let widget = Paragraph::new(text).block(block);
+        return Some(widget);
+    }
+
     pub fn reset_table_widget(&mut self, selected_table: String, table_model: TableModel) {
         self.record_widget = TableRecordWdg::new(selected_table.clone(), table_model.record);
-        self.column_widget = TableColumnWdg::new(selected_table.clone(), table_model.column);
+        self.column_widget = TableColumnWdg::new(table_model.column);
+    }
+
+    /// The primary key of the highlighted record, or why it cannot be changed.
+    pub fn selected_primary_key(&self) -> Result<Vec<ColumnValue>, String> {
+        let table_column_model = self.column_widget.table_column_model();
+        if table_column_model.primary_key_columns().is_empty() {
+            return Err(format!(
+                "Table {} has no primary key, so its rows cannot be changed",
+                self.record_widget.current_table
+            ));
+        }
+        return match self.record_widget.selected_record() {
+            Some((_, record)) => {
+                Ok(table_column_model.primary_key(self.record_widget.headers(), record))
+            }
+            None => Err(String::from("No row is selected")),
+        };
+    }
+
+    /// Describes an edit of the highlighted record cell, or why it cannot be edited.
+    pub fn selected_cell_edit(&self) -> Result<CellEdit, String> {
+        let primary_key = self.selected_primary_key()?;
+        let column = self.record_widget.headers()[self.record_widget.selected_column_index].clone();
+        let value = match self.record_widget.selected_record() {
+            Some((_, record)) => record[self.record_widget.selected_column_index].clone(),
+            None => return Err(String::from("No row is selected")),
+        };
+        // Binary values are shown as hex, which would be written back as text
+        if let CellValue::Bytes(_) = value {
+            return Err(format!(
+                "{} holds binary data, which cannot be edited here",
+                column
+            ));
+        }
+
+        Ok(CellEdit {
+            table: self.record_widget.current_table.clone(),
+            target: ColumnValue {
+                column_type: self.column_widget.table_column_model().column_type(&column),
+                value: value,
+                column: column,
+            },
+            primary_key: primary_key,
+        })
     }
 
     pub fn move_up(&mut self, table_mode: &TableMode) {
@@ -79,3 +195,36 @@ impl<'a> TableWdg<'a> {
         };
     }
 }
+
+/// Width of the widest number in each column, so numbers can be right-aligned against each other.
+pub fn numeric_column_widths(records: &[Vec<CellValue>]) -> Vec<usize> {
+    let mut widths = vec![];
+    for record in records.iter() {
+        widths.resize(widths.len().max(record.len()), 0);
+        for (column_index, value) in record.iter().enumerate() {
+            if value.is_numeric() {
+                widths[column_index] = widths[column_index].max(value.preview().len());
+            }
+        }
+    }
+    return widths;
+}
+
+pub fn value_cell<'a>(value: &CellValue, numeric_width: usize, is_selected: bool) -> Cell<'a> {
+    let text = if value.is_numeric() {
+        format!("{:>width$}", value.preview(), width = numeric_width)
+    } else {
+        value.preview()
+    };
+    let mut style = if value.is_null() {
+        Style::default()
+            .fg(Color::DarkGray)
+            .add_modifier(Modifier::DIM)
+    } else {
+        Style::default()
+    };
+    if is_selected {
+        style = style.bg(Color::Blue);
+    }
+    return Cell::from(text).style(style);
+}
@@ -1,16 +1,15 @@
 use tui::{
     layout::Constraint,
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     widgets::{Block, Borders, Cell, Row, Table, TableState},
 };
 
 use crate::model::table::TableColumnModel;
 
-use super::table::{SelectableRange, VisibleRange};
+use super::table::{numeric_column_widths, value_cell, SelectableRange, VisibleRange};
 
 pub struct TableColumnWdg<'a> {
     pub title: &'a str,
-    pub current_table: String,
     table_column_model: TableColumnModel,
     selectable_range: SelectableRange,
     visible_range: VisibleRange,
@@ -19,7 +18,7 @@ pub struct TableColumnWdg<'a> {
 }
 
 impl<'a> TableColumnWdg<'a> {
-    pub fn new(current_table: String, table_column_model: TableColumnModel) -> Self {
+    pub fn new(table_column_model: TableColumnModel) -> Self {
         let selectable_range = SelectableRange {
             width: table_column_model.headers.len().saturating_sub(1),
             height: table_column_model.columns.len().saturating_sub(1),
@@ -33,7 +32,6 @@ impl<'a> TableColumnWdg<'a> {
 
         Self {
             title: "Columns",
-            current_table: current_table,
             table_column_model: table_column_model,
             selectable_range: selectable_range,
             visible_range: visible_range,
@@ -42,6 +40,10 @@ impl<'a> TableColumnWdg<'a> {
         }
     }
 
+    pub fn table_column_model(&self) -> &TableColumnModel {
+        return &self.table_column_model;
+    }
+
     pub fn widget(&self) -> Table<'a> {
         let block = Block::default()
             .title(self.title.to_string())
@@ -57,6 +59,7 @@ impl<'a> TableColumnWdg<'a> {
         .height(1)
         .bottom_margin(1);
 
+        let numeric_widths = numeric_column_widths(&self.table_column_model.columns);
         let record_layout =
             self.table_column_model
                 .columns
@@ -67,16 +70,13 @@ impl<'a> TableColumnWdg<'a> {
                         .iter()
                         .enumerate()
                         .map(|(column_idx, c)| {
-                            Cell::from(c.to_string()).style(
-                                if column_idx
+                            value_cell(
+                                c,
+                                numeric_widths[self.visible_range.begin_column_index + column_idx],
+                                column_idx
                                     == self.selected_column_index
                                         - self.visible_range.begin_column_index
-                                    && Some(row_index) == self.select_row_list_state.selected()
-                                {
-                                    Style::default().bg(Color::Blue)
-                                } else {
-                                    Style::default()
-                                },
+                                    && Some(row_index) == self.select_row_list_state.selected(),
                             )
                         });
                     Row::new(cells).bottom_margin(1)
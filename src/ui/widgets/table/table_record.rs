@@ -4,9 +4,16 @@ use tui::{
     widgets::{Block, Borders, Cell, Row, Table, TableState},
 };
 
-use crate::model::table::TableRecordModel;
+use crate::model::{
+    cell::CellValue,
+    change_set::ChangeSet,
+    table::{SortOrder, TableRecordModel},
+};
+
+use super::table::{numeric_column_widths, value_cell, SelectableRange, VisibleRange};
 
-use super::table::{SelectableRange, VisibleRange};
+/// Fetch the next page once the cursor is this close to the last loaded row.
+const PREFETCH_THRESHOLD: usize = 20;
 
 pub struct TableRecordWdg<'a> {
     pub title: &'a str,
@@ -42,45 +49,80 @@ impl<'a> TableRecordWdg<'a> {
         }
     }
 
-    pub fn widget(&self) -> Table<'a> {
+    pub fn new_query_result(table_record_model: TableRecordModel) -> Self {
+        let mut widget = Self::new(String::new(), table_record_model);
+        widget.title = "Result";
+        return widget;
+    }
+
+    /// The grid, with cells and rows staged in `change_set` highlighted in place.
+    pub fn widget(&self, change_set: Option<&ChangeSet>) -> Table<'a> {
         let block = Block::default()
-            .title(self.title.to_string())
+            .title(self.status_title(change_set))
             .borders(Borders::ALL);
 
-        let header_layout = Row::new(
-            self.table_record_model.headers[self.visible_range.begin_column_index..]
-                .iter()
-                .map(|h| {
-                    Cell::from(h.to_string()).style(Style::default().add_modifier(Modifier::BOLD))
-                }),
-        )
+        let headers = &self.table_record_model.headers;
+        let header_layout = Row::new(headers[self.visible_range.begin_column_index..].iter().map(
+            |h| {
+                Cell::from(self.header_title(h))
+                    .style(Style::default().add_modifier(Modifier::BOLD))
+            },
+        ))
         .height(1)
         .bottom_margin(1);
 
-        let record_layout =
-            self.table_record_model
-                .records
-                .iter()
-                .enumerate()
-                .map(|(row_index, item)| {
-                    let cells = item[self.visible_range.begin_column_index..]
-                        .iter()
-                        .enumerate()
-                        .map(|(column_idx, c)| {
-                            Cell::from(c.to_string()).style(
-                                if column_idx
-                                    == self.selected_column_index
-                                        - self.visible_range.begin_column_index
-                                    && Some(row_index) == self.select_row_list_state.selected()
-                                {
-                                    Style::default().bg(Color::Blue)
-                                } else {
-                                    Style::default()
-                                },
-                            )
+        let numeric_widths = numeric_column_widths(&self.table_record_model.records);
+        let mut record_layout: Vec<Row> = self
+            .table_record_model
+            .records
+            .iter()
+            .enumerate()
+            .map(|(row_index, item)| {
+                let cells = item[self.visible_range.begin_column_index..]
+                    .iter()
+                    .enumerate()
+                    .map(|(column_idx, c)| {
+                        let column_index = self.visible_range.begin_column_index + column_idx;
+                        let is_selected = column_index == self.selected_column_index
+                            && Some(row_index) == self.select_row_list_state.selected();
+                        let pending = change_set.and_then(|change_set| {
+                            change_set.pending_cell(headers, item, &headers[column_index])
                         });
-                    Row::new(cells).bottom_margin(1)
-                });
+                        match pending {
+                            Some(value) if !is_selected => {
+                                value_cell(value, numeric_widths[column_index], is_selected)
+                                    .style(Style::default().fg(Color::Black).bg(Color::Yellow))
+                            }
+                            Some(value) => {
+                                value_cell(value, numeric_widths[column_index], is_selected)
+                            }
+                            None => value_cell(c, numeric_widths[column_index], is_selected),
+                        }
+                    });
+                let row = Row::new(cells).bottom_margin(1);
+                match change_set {
+                    Some(change_set) if change_set.is_deleted(headers, item) => row.style(
+                        Style::default()
+                            .fg(Color::Red)
+                            .add_modifier(Modifier::CROSSED_OUT),
+                    ),
+                    _ => row,
+                }
+            })
+            .collect();
+        if let Some(change_set) = change_set {
+            for item in change_set.inserted_records(headers) {
+                let cells: Vec<Cell> = item[self.visible_range.begin_column_index..]
+                    .iter()
+                    .map(|c| Cell::from(c.preview()))
+                    .collect();
+                record_layout.push(
+                    Row::new(cells)
+                        .bottom_margin(1)
+                        .style(Style::default().fg(Color::Green)),
+                );
+            }
+        }
 
         let widget = Table::new(record_layout)
             .header(header_layout)
@@ -156,6 +198,93 @@ impl<'a> TableRecordWdg<'a> {
         }
     }
 
+    pub fn is_near_loaded_end(&self) -> bool {
+        if self.table_record_model.is_fully_loaded() {
+            return false;
+        }
+        return match self.select_row_list_state.selected() {
+            Some(selected) => {
+                selected + PREFETCH_THRESHOLD >= self.table_record_model.records.len()
+            }
+            None => false,
+        };
+    }
+
+    /// Appends a page fetched with `TableRecordModel::fetch_next_page` from row `offset`.
+    pub fn add_page(&mut self, offset: usize, headers: Vec<String>, records: Vec<Vec<CellValue>>) {
+        self.table_record_model.add_page(offset, headers, records);
+        self.reset_selectable_range();
+    }
+
+    /// Shows the rows loaded again for a new sort or filter, from the top.
+    pub fn replace_records(&mut self, table_record_model: TableRecordModel) {
+        self.table_record_model = table_record_model;
+        self.select_row_list_state.select(Some(0));
+        self.reset_selectable_range();
+    }
+
+    /// The header of the highlighted column.
+    pub fn selected_column(&self) -> Option<&String> {
+        return self
+            .table_record_model
+            .headers
+            .get(self.selected_column_index);
+    }
+
+    fn reset_selectable_range(&mut self) {
+        self.selectable_range = SelectableRange {
+            width: self.table_record_model.headers.len().saturating_sub(1),
+            height: self.table_record_model.records.len().saturating_sub(1),
+        };
+    }
+
+    fn header_title(&self, header: &str) -> String {
+        match &self.table_record_model.sort {
+            Some(sort) if sort.column == header => match sort.order {
+                SortOrder::Asc => format!("{} ▲", header),
+                SortOrder::Desc => format!("{} ▼", header),
+            },
+            _ => header.to_string(),
+        }
+    }
+
+    fn status_title(&self, change_set: Option<&ChangeSet>) -> String {
+        let loaded = self.table_record_model.records.len();
+        let status = if loaded == 0 {
+            format!("{} [0 of {}]", self.title, self.table_record_model.total)
+        } else {
+            format!(
+                "{} [1-{} of {}]",
+                self.title, loaded, self.table_record_model.total
+            )
+        };
+        return match change_set {
+            Some(change_set) if !change_set.is_empty() => format!(
+                "{} [{} pending, p: review]",
+                status,
+                change_set.changes.len()
+            ),
+            _ => status,
+        };
+    }
+
+    pub fn table_record_model(&self) -> &TableRecordModel {
+        return &self.table_record_model;
+    }
+
+    pub fn headers(&self) -> &[String] {
+        return &self.table_record_model.headers;
+    }
+
+    pub fn selected_record(&self) -> Option<(usize, &Vec<CellValue>)> {
+        let index = self.select_row_list_state.selected()?;
+        return self
+            .table_record_model
+            .records
+            .get(index)
+            .map(|record| (index, record));
+    }
+
     pub fn is_current_table(&self, selected_table: String) -> bool {
         return selected_table == self.current_table;
     }
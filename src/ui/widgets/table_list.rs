@@ -14,8 +14,9 @@ pub struct TableListWdg<'a> {
 impl<'a> TableListWdg<'a> {
     pub fn new(tables: Vec<String>) -> Self {
         let mut table_select_state = ListState::default();
-        table_select_state.select(Some(0));
-        let current_table = tables[0].clone();
+        table_select_state.select(if tables.is_empty() { None } else { Some(0) });
+        // Empty while the first connection is still being picked
+        let current_table = tables.first().cloned().unwrap_or_default();
 
         Self {
             title: "Tables",
@@ -79,7 +80,22 @@ impl<'a> TableListWdg<'a> {
         }
     }
 
+    /// Selects `table` and makes it the current one. Returns false if there is no such table.
+    pub fn select_table(&mut self, table: &str) -> bool {
+        match self.tables.iter().position(|name| name == table) {
+            Some(index) => {
+                self.table_select_state.select(Some(index));
+                self.current_table = table.to_string();
+                return true;
+            }
+            None => return false,
+        }
+    }
+
+    /// Replaces the list, e.g. after switching database, and selects its first table.
     pub fn change_tables(&mut self, new_tables: Vec<String>) {
+        self.table_select_state
+            .select(if new_tables.is_empty() { None } else { Some(0) });
         self.tables = new_tables;
     }
 }
@@ -0,0 +1,229 @@
+use unicode_width::UnicodeWidthChar;
+
+/// A multi-line text with a cursor, edited in place.
+/// The cursor column counts characters, not bytes, so multi-byte text is safe to edit.
+pub struct TextBuffer {
+    lines: Vec<String>,
+    row: usize,
+    col: usize,
+}
+
+impl TextBuffer {
+    pub fn new() -> Self {
+        Self {
+            lines: vec![String::new()],
+            row: 0,
+            col: 0,
+        }
+    }
+
+    pub fn text(&self) -> String {
+        return self.lines.join("\n");
+    }
+
+    /// Replaces the whole text and puts the cursor at its end.
+    pub fn set_text(&mut self, text: &str) {
+        self.lines = text
+            .split('\n')
+            .map(|line| line.trim_end_matches('\r').to_string())
+            .collect();
+        self.row = self.lines.len() - 1;
+        self.col = self.line_len(self.row);
+    }
+
+    pub fn is_on_first_line(&self) -> bool {
+        return self.row == 0;
+    }
+
+    pub fn is_on_last_line(&self) -> bool {
+        return self.row + 1 == self.lines.len();
+    }
+
+    fn line_len(&self, row: usize) -> usize {
+        return self.lines[row].chars().count();
+    }
+
+    fn byte_index(&self) -> usize {
+        return self.lines[self.row]
+            .char_indices()
+            .nth(self.col)
+            .map(|(index, _)| index)
+            .unwrap_or_else(|| self.lines[self.row].len());
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        if c == '\n' {
+            self.insert_newline();
+            return;
+        }
+        let index = self.byte_index();
+        self.lines[self.row].insert(index, c);
+        self.col += 1;
+    }
+
+    pub fn insert_newline(&mut self) {
+        let index = self.byte_index();
+        let rest = self.lines[self.row].split_off(index);
+        self.lines.insert(self.row + 1, rest);
+        self.row += 1;
+        self.col = 0;
+    }
+
+    /// Deletes the character before the cursor, joining lines at the start of a line.
+    pub fn backspace(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+            let index = self.byte_index();
+            self.lines[self.row].remove(index);
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.col = self.line_len(self.row);
+            self.lines[self.row].push_str(&line);
+        }
+    }
+
+    /// Deletes the character under the cursor, joining lines at the end of a line.
+    pub fn delete(&mut self) {
+        if self.col < self.line_len(self.row) {
+            let index = self.byte_index();
+            self.lines[self.row].remove(index);
+        } else if self.row + 1 < self.lines.len() {
+            let next = self.lines.remove(self.row + 1);
+            self.lines[self.row].push_str(&next);
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+        } else if self.row > 0 {
+            self.row -= 1;
+            self.col = self.line_len(self.row);
+        }
+    }
+
+    pub fn move_right(&mut self) {
+        if self.col < self.line_len(self.row) {
+            self.col += 1;
+        } else if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = 0;
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        if self.row > 0 {
+            self.row -= 1;
+            self.col = self.col.min(self.line_len(self.row));
+        }
+    }
+
+    pub fn move_down(&mut self) {
+        if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = self.col.min(self.line_len(self.row));
+        }
+    }
+
+    pub fn move_home(&mut self) {
+        self.col = 0;
+    }
+
+    pub fn move_end(&mut self) {
+        self.col = self.line_len(self.row);
+    }
+
+    /// Moves to the start of the word before the cursor.
+    pub fn move_word_left(&mut self) {
+        if self.col == 0 {
+            self.move_left();
+            return;
+        }
+        let chars: Vec<char> = self.lines[self.row].chars().collect();
+        let mut col = self.col;
+        while col > 0 && !is_word_char(chars[col - 1]) {
+            col -= 1;
+        }
+        while col > 0 && is_word_char(chars[col - 1]) {
+            col -= 1;
+        }
+        self.col = col;
+    }
+
+    /// Moves past the end of the word after the cursor.
+    pub fn move_word_right(&mut self) {
+        let chars: Vec<char> = self.lines[self.row].chars().collect();
+        if self.col == chars.len() {
+            self.move_right();
+            return;
+        }
+        let mut col = self.col;
+        while col < chars.len() && !is_word_char(chars[col]) {
+            col += 1;
+        }
+        while col < chars.len() && is_word_char(chars[col]) {
+            col += 1;
+        }
+        self.col = col;
+    }
+
+    /// The word the cursor is at the end of, and whether a `.` comes right before it.
+    pub fn word_before_cursor(&self) -> (String, bool) {
+        let chars: Vec<char> = self.lines[self.row].chars().take(self.col).collect();
+        let start = chars
+            .iter()
+            .rposition(|c| !is_word_char(*c))
+            .map_or(0, |index| index + 1);
+        let after_dot = start > 0 && chars[start - 1] == '.';
+        return (chars[start..].iter().collect(), after_dot);
+    }
+
+    /// Replaces the word before the cursor with `word`.
+    pub fn complete_word(&mut self, word: &str) {
+        let (prefix, _) = self.word_before_cursor();
+        for _ in prefix.chars() {
+            self.backspace();
+        }
+        for c in word.chars() {
+            self.insert_char(c);
+        }
+    }
+
+    /// Breaks the text into screen rows of at most `width` columns and returns them
+    /// with the cursor's (x, y) position among those rows.
+    pub fn wrapped(&self, width: usize) -> (Vec<String>, (usize, usize)) {
+        let width = width.max(1);
+        let mut rows = vec![];
+        let mut cursor = (0, 0);
+        for (row, line) in self.lines.iter().enumerate() {
+            let mut text = String::new();
+            let mut text_width = 0;
+            for (col, c) in line.chars().enumerate() {
+                let char_width = c.width().unwrap_or(0);
+                if text_width + char_width > width && text_width > 0 {
+                    rows.push(std::mem::take(&mut text));
+                    text_width = 0;
+                }
+                if row == self.row && col == self.col {
+                    cursor = (text_width, rows.len());
+                }
+                text.push(c);
+                text_width += char_width;
+            }
+            if row == self.row && self.col == self.line_len(row) {
+                cursor = if text_width >= width {
+                    (0, rows.len() + 1)
+                } else {
+                    (text_width, rows.len())
+                };
+            }
+            rows.push(text);
+        }
+        return (rows, cursor);
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    return c.is_alphanumeric() || c == '_';
+}
@@ -1,46 +1,467 @@
-use sqlx::{mysql::MySqlRow, Row};
-
-pub fn convert_column_value_to_string(row: &MySqlRow, column_name: &str) -> String {
-    if let Ok(value) = row.try_get(column_name) {
-        let value: String = value;
-        value.to_string()
-    } else if let Ok(value) = row.try_get(column_name) {
-        let value: &str = value;
-        value.to_string()
-    } else if let Ok(value) = row.try_get(column_name) {
-        let value: i8 = value;
-        value.to_string()
-    } else if let Ok(value) = row.try_get(column_name) {
-        let value: i16 = value;
-        value.to_string()
-    } else if let Ok(value) = row.try_get(column_name) {
-        let value: i32 = value;
-        value.to_string()
-    } else if let Ok(value) = row.try_get(column_name) {
-        let value: i64 = value;
-        value.to_string()
-    } else if let Ok(value) = row.try_get(column_name) {
-        let value: u8 = value;
-        value.to_string()
-    } else if let Ok(value) = row.try_get(column_name) {
-        let value: u16 = value;
-        value.to_string()
-    } else if let Ok(value) = row.try_get(column_name) {
-        let value: u32 = value;
-        value.to_string()
-    } else if let Ok(value) = row.try_get(column_name) {
-        let value: u64 = value;
-        value.to_string()
-    } else if let Ok(value) = row.try_get(column_name) {
-        let value: bool = value;
-        value.to_string()
-    } else if let Ok(value) = row.try_get(column_name) {
-        let value: chrono::DateTime<chrono::Utc> = value;
-        value.to_string()
-    } else if let Ok(value) = row.try_get(column_name) {
-        let value: serde_json::Value = value;
-        value.to_string()
+use std::net::IpAddr;
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use sqlx::{mysql::MySqlRow, postgres::PgRow, sqlite::SqliteRow, Column, Row, TypeInfo, ValueRef};
+
+use crate::model::cell::CellValue;
+
+pub fn decode_mysql_cell_value(row: &MySqlRow, index: usize) -> CellValue {
+    match row.try_get_raw(index) {
+        Ok(value) if !value.is_null() => {}
+        _ => return CellValue::Null,
+    }
+
+    // The checked `try_get` rejects text in collations sqlx doesn't know about,
+    // so decode by the column's declared type instead.
+    let decoded = match row.column(index).type_info().name() {
+        "BOOLEAN" => row.try_get_unchecked(index).map(CellValue::Bool),
+        "TINYINT" | "SMALLINT" | "MEDIUMINT" | "INT" | "BIGINT" => {
+            row.try_get_unchecked(index).map(CellValue::Int)
+        }
+        "TINYINT UNSIGNED" | "SMALLINT UNSIGNED" | "MEDIUMINT UNSIGNED" | "INT UNSIGNED"
+        | "BIGINT UNSIGNED" | "YEAR" | "BIT" => row.try_get_unchecked(index).map(CellValue::UInt),
+        "FLOAT" => row
+            .try_get_unchecked::<f32, _>(index)
+            .map(|value| CellValue::Float(value as f64)),
+        "DOUBLE" => row.try_get_unchecked(index).map(CellValue::Float),
+        "DECIMAL" => row.try_get_unchecked(index).map(CellValue::Decimal),
+        "DATE" => row.try_get_unchecked(index).map(CellValue::Date),
+        "TIME" => row.try_get_unchecked(index).map(CellValue::Time),
+        "DATETIME" | "TIMESTAMP" => row.try_get_unchecked(index).map(CellValue::DateTime),
+        "JSON" => row.try_get_unchecked(index).map(CellValue::Json),
+        "BINARY" | "VARBINARY" | "TINYBLOB" | "BLOB" | "MEDIUMBLOB" | "LONGBLOB" | "GEOMETRY" => {
+            row.try_get_unchecked(index).map(CellValue::Bytes)
+        }
+        _ => row.try_get_unchecked(index).map(CellValue::Text),
+    };
+
+    return decoded.unwrap_or_else(|_| decode_raw_bytes(row.try_get_unchecked(index)));
+}
+
+pub fn decode_pg_cell_value(row: &PgRow, index: usize) -> CellValue {
+    match row.try_get_raw(index) {
+        Ok(value) if !value.is_null() => {}
+        _ => return CellValue::Null,
+    }
+
+    let decoded = match row.column(index).type_info().name() {
+        "BOOL" => row.try_get(index).map(CellValue::Bool),
+        "INT2" => row
+            .try_get::<i16, _>(index)
+            .map(|value| CellValue::Int(value as i64)),
+        "INT4" => row
+            .try_get::<i32, _>(index)
+            .map(|value| CellValue::Int(value as i64)),
+        "INT8" => row.try_get(index).map(CellValue::Int),
+        "OID" => row
+            .try_get_unchecked::<i32, _>(index)
+            .map(|value| CellValue::UInt(value as u32 as u64)),
+        "FLOAT4" => row
+            .try_get::<f32, _>(index)
+            .map(|value| CellValue::Float(value as f64)),
+        "FLOAT8" => row.try_get(index).map(CellValue::Float),
+        // Exactly as Postgres prints it, however many digits, and NaN or infinite
+        "NUMERIC" => decode_pg_text(row, index).map(CellValue::Decimal),
+        "DATE" => row.try_get(index).map(CellValue::Date),
+        "TIME" => row.try_get(index).map(CellValue::Time),
+        "TIMESTAMP" => row.try_get(index).map(CellValue::DateTime),
+        "TIMESTAMPTZ" => row.try_get(index).map(CellValue::Timestamp),
+        "JSON" | "JSONB" => row.try_get(index).map(CellValue::Json),
+        "BYTEA" => row.try_get(index).map(CellValue::Bytes),
+        // As text, which the CAST in the client's placeholders turns back into the type
+        "UUID" | "INTERVAL" | "INET" | "CIDR" | "MACADDR" => {
+            decode_pg_text(row, index).map(CellValue::Text)
+        }
+        name if name.ends_with("[]") => decode_pg_text(row, index).map(CellValue::Text),
+        // Enums and other text-like types arrive as their UTF-8 label.
+        _ => row.try_get_unchecked(index).map(CellValue::Text),
+    };
+
+    return decoded.unwrap_or_else(|_| decode_raw_bytes(row.try_get_unchecked(index)));
+}
+
+/// Decodes the value to the text Postgres would print for it.
+fn decode_pg_text(row: &PgRow, index: usize) -> Result<String, sqlx::Error> {
+    let type_name = row.column(index).type_info().name();
+    let bytes: Vec<u8> = row.try_get_unchecked(index)?;
+    return pg_binary_text(type_name, &bytes)
+        .ok_or_else(|| sqlx::Error::Decode(format!("Cannot decode {}", type_name).into()));
+}
+
+/// The text form of a value sent in Postgres' binary format, or None for a type it does
+/// not know or bytes it cannot read.
+fn pg_binary_text(type_name: &str, bytes: &[u8]) -> Option<String> {
+    if type_name.ends_with("[]") {
+        return pg_array_text(bytes);
+    }
+    let text = match type_name {
+        "BOOL" => String::from(if *bytes.first()? == 0 { "f" } else { "t" }),
+        "INT2" => i16::from_be_bytes(read_be(bytes, 0)?).to_string(),
+        "INT4" => i32::from_be_bytes(read_be(bytes, 0)?).to_string(),
+        "INT8" => i64::from_be_bytes(read_be(bytes, 0)?).to_string(),
+        "OID" => u32::from_be_bytes(read_be(bytes, 0)?).to_string(),
+        "FLOAT4" => pg_float_text(f32::from_be_bytes(read_be(bytes, 0)?) as f64),
+        "FLOAT8" => pg_float_text(f64::from_be_bytes(read_be(bytes, 0)?)),
+        "NUMERIC" => pg_numeric_text(bytes)?,
+        "UUID" => uuid::Uuid::from_slice(bytes).ok()?.to_string(),
+        "INTERVAL" => pg_interval_text(
+            i64::from_be_bytes(read_be(bytes, 0)?),
+            i32::from_be_bytes(read_be(bytes, 8)?),
+            i32::from_be_bytes(read_be(bytes, 12)?),
+        ),
+        "INET" | "CIDR" => {
+            let address = match (*bytes.first()?, bytes.get(4..)?) {
+                (2, address) => IpAddr::from(<[u8; 4]>::try_from(address).ok()?),
+                (3, address) => IpAddr::from(<[u8; 16]>::try_from(address).ok()?),
+                _ => return None,
+            };
+            let bits = *bytes.get(1)?;
+            let full_bits = if address.is_ipv4() { 32 } else { 128 };
+            if type_name == "CIDR" || bits != full_bits {
+                format!("{}/{}", address, bits)
+            } else {
+                address.to_string()
+            }
+        }
+        "MACADDR" if bytes.len() == 6 => bytes
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<Vec<String>>()
+            .join(":"),
+        "DATE" => {
+            let days = i32::from_be_bytes(read_be(bytes, 0)?);
+            pg_epoch()
+                .date()
+                .checked_add_signed(Duration::days(days as i64))?
+                .to_string()
+        }
+        "TIME" => {
+            let micros = i64::from_be_bytes(read_be(bytes, 0)?);
+            let time = NaiveTime::from_hms(0, 0, 0) + Duration::microseconds(micros);
+            time.format("%H:%M:%S%.f").to_string()
+        }
+        "TIMESTAMP" | "TIMESTAMPTZ" => {
+            let micros = i64::from_be_bytes(read_be(bytes, 0)?);
+            let timestamp = pg_epoch().checked_add_signed(Duration::microseconds(micros))?;
+            let zone = if type_name == "TIMESTAMPTZ" {
+                "+00"
+            } else {
+                ""
+            };
+            format!("{}{}", timestamp.format("%Y-%m-%d %H:%M:%S%.f"), zone)
+        }
+        "BYTEA" => format!(
+            "\\x{}",
+            bytes
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<String>()
+        ),
+        // JSONB starts with its format version
+        "JSONB" => String::from_utf8(bytes.get(1..)?.to_vec()).ok()?,
+        "TEXT" | "VARCHAR" | "BPCHAR" | "NAME" | "CHAR" | "JSON" => {
+            String::from_utf8(bytes.to_vec()).ok()?
+        }
+        _ => return None,
+    };
+    return Some(text);
+}
+
+fn read_be<const N: usize>(bytes: &[u8], at: usize) -> Option<[u8; N]> {
+    return bytes.get(at..at + N)?.try_into().ok();
+}
+
+/// Midnight of 2000-01-01, which Postgres counts dates and timestamps from.
+fn pg_epoch() -> NaiveDateTime {
+    return NaiveDate::from_ymd(2000, 1, 1).and_hms(0, 0, 0);
+}
+
+fn pg_float_text(value: f64) -> String {
+    match value {
+        value if value == f64::INFINITY => String::from("Infinity"),
+        value if value == f64::NEG_INFINITY => String::from("-Infinity"),
+        value => value.to_string(),
+    }
+}
+
+/// A NUMERIC is a sign, a scale and base-10000 digits, the first `weight` of them before
+/// the decimal point.
+fn pg_numeric_text(bytes: &[u8]) -> Option<String> {
+    let word = |index: usize| read_be(bytes, index * 2).map(i16::from_be_bytes);
+    let (digit_count, weight, sign, scale) = (word(0)?, word(1)?, word(2)? as u16, word(3)?);
+    let negative = match sign {
+        0x0000 => false,
+        0x4000 => true,
+        0xC000 => return Some(String::from("NaN")),
+        0xD000 => return Some(String::from("Infinity")),
+        0xF000 => return Some(String::from("-Infinity")),
+        _ => return None,
+    };
+    let digits = (0..digit_count.max(0) as usize)
+        .map(|index| word(4 + index))
+        .collect::<Option<Vec<i16>>>()?;
+    let digit = |index: i32| match usize::try_from(index) {
+        Ok(index) => digits.get(index).copied().unwrap_or(0),
+        Err(_) => 0,
+    };
+
+    let mut text = String::from(if negative { "-" } else { "" });
+    if weight < 0 {
+        text.push('0');
     } else {
-        String::from("NULL")
+        text.push_str(&digit(0).to_string());
+        for index in 1..=weight as i32 {
+            text.push_str(&format!("{:04}", digit(index)));
+        }
+    }
+    if scale > 0 {
+        let mut fraction = String::new();
+        let mut index = weight as i32 + 1;
+        while fraction.len() < scale as usize {
+            fraction.push_str(&format!("{:04}", digit(index)));
+            index += 1;
+        }
+        fraction.truncate(scale as usize);
+        text.push('.');
+        text.push_str(&fraction);
+    }
+    return Some(text);
+}
+
+fn pg_interval_text(micros: i64, days: i32, months: i32) -> String {
+    let plural = |count: i32, unit: &str| match count {
+        1 => format!("1 {}", unit),
+        count => format!("{} {}s", count, unit),
+    };
+    let mut parts = vec![];
+    for (count, unit) in [(months / 12, "year"), (months % 12, "mon"), (days, "day")] {
+        if count != 0 {
+            parts.push(plural(count, unit));
+        }
+    }
+    if micros != 0 || parts.is_empty() {
+        let sign = if micros < 0 { "-" } else { "" };
+        let seconds = micros.unsigned_abs() / 1_000_000;
+        let mut time = format!(
+            "{}{:02}:{:02}:{:02}",
+            sign,
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        );
+        let fraction = micros.unsigned_abs() % 1_000_000;
+        if fraction != 0 {
+            time.push_str(format!(".{:06}", fraction).trim_end_matches('0'));
+        }
+        parts.push(time);
+    }
+    return parts.join(" ");
+}
+
+/// An array's header gives its dimensions and element type, followed by the elements,
+/// each as a length (-1 for NULL) and the element's own binary value.
+fn pg_array_text(bytes: &[u8]) -> Option<String> {
+    let dimensions = usize::try_from(i32::from_be_bytes(read_be(bytes, 0)?)).ok()?;
+    let element_type = pg_type_name(u32::from_be_bytes(read_be(bytes, 8)?))?;
+    if dimensions == 0 {
+        return Some(String::from("{}"));
+    }
+    let mut lengths = vec![];
+    let mut bounds = String::new();
+    let mut default_bounds = true;
+    for dimension in 0..dimensions {
+        let length = i32::from_be_bytes(read_be(bytes, 12 + dimension * 8)?);
+        let lower_bound = i32::from_be_bytes(read_be(bytes, 16 + dimension * 8)?);
+        default_bounds &= lower_bound == 1;
+        bounds.push_str(&format!("[{}:{}]", lower_bound, lower_bound + length - 1));
+        lengths.push(usize::try_from(length).ok()?);
+    }
+
+    let mut at = 12 + dimensions * 8;
+    let mut elements = vec![];
+    for _ in 0..lengths.iter().product() {
+        let length = i32::from_be_bytes(read_be(bytes, at)?);
+        at += 4;
+        let length = match usize::try_from(length) {
+            Ok(length) => length,
+            Err(_) => {
+                elements.push(String::from("NULL"));
+                continue;
+            }
+        };
+        let element = pg_binary_text(element_type, bytes.get(at..at + length)?)?;
+        at += length;
+        elements.push(quote_pg_array_element(element));
+    }
+
+    let text = nest_pg_array(&lengths, &mut elements.into_iter());
+    if default_bounds {
+        return Some(text);
+    }
+    return Some(format!("{}={}", bounds, text));
+}
+
+fn nest_pg_array(lengths: &[usize], elements: &mut impl Iterator<Item = String>) -> String {
+    let items: Vec<String> = match lengths {
+        [] => vec![],
+        [length] => elements.take(*length).collect(),
+        [length, inner @ ..] => (0..*length)
+            .map(|_| nest_pg_array(inner, elements))
+            .collect(),
+    };
+    return format!("{{{}}}", items.join(","));
+}
+
+fn quote_pg_array_element(element: String) -> String {
+    let needs_quotes = element.is_empty()
+        || element.eq_ignore_ascii_case("NULL")
+        || element
+            .chars()
+            .any(|c| c.is_whitespace() || "{}\",\\".contains(c));
+    if !needs_quotes {
+        return element;
+    }
+    return format!("\"{}\"", element.replace('\\', "\\\\").replace('"', "\\\""));
+}
+
+/// The name sqlx gives the built-in type with `oid`, for the array element types read.
+fn pg_type_name(oid: u32) -> Option<&'static str> {
+    let name = match oid {
+        16 => "BOOL",
+        17 => "BYTEA",
+        18 => "CHAR",
+        19 => "NAME",
+        20 => "INT8",
+        21 => "INT2",
+        23 => "INT4",
+        25 => "TEXT",
+        26 => "OID",
+        114 => "JSON",
+        650 => "CIDR",
+        700 => "FLOAT4",
+        701 => "FLOAT8",
+        829 => "MACADDR",
+        869 => "INET",
+        1042 => "BPCHAR",
+        1043 => "VARCHAR",
+        1082 => "DATE",
+        1083 => "TIME",
+        1114 => "TIMESTAMP",
+        1184 => "TIMESTAMPTZ",
+        1186 => "INTERVAL",
+        1700 => "NUMERIC",
+        2950 => "UUID",
+        3802 => "JSONB",
+        _ => return None,
+    };
+    return Some(name);
+}
+
+pub fn decode_sqlite_cell_value(row: &SqliteRow, index: usize) -> CellValue {
+    // SQLite is dynamically typed, so go by the stored value rather than the declared column type.
+    let type_name = match row.try_get_raw(index) {
+        Ok(value) if !value.is_null() => value.type_info().name().to_string(),
+        _ => return CellValue::Null,
+    };
+
+    let decoded = match type_name.as_str() {
+        "INTEGER" => row.try_get_unchecked(index).map(CellValue::Int),
+        "REAL" => row.try_get_unchecked(index).map(CellValue::Float),
+        "BLOB" => row.try_get_unchecked(index).map(CellValue::Bytes),
+        _ => row.try_get_unchecked(index).map(CellValue::Text),
+    };
+
+    return decoded.unwrap_or_else(|_| decode_raw_bytes(row.try_get_unchecked(index)));
+}
+
+fn decode_raw_bytes(bytes: Result<Vec<u8>, sqlx::Error>) -> CellValue {
+    match bytes {
+        Ok(bytes) => match String::from_utf8(bytes) {
+            Ok(text) => CellValue::Text(text),
+            Err(e) => CellValue::Bytes(e.into_bytes()),
+        },
+        Err(_) => CellValue::Null,
+    }
+}
+
+pub fn quote_identifier(identifier: &str, quote: char) -> String {
+    let escaped_quote = format!("{}{}", quote, quote);
+    return format!(
+        "{}{}{}",
+        quote,
+        identifier.replace(quote, &escaped_quote),
+        quote
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::pg_binary_text;
+
+    fn numeric(weight: i16, sign: u16, scale: i16, digits: &[i16]) -> Vec<u8> {
+        let mut bytes = vec![];
+        for word in [digits.len() as i16, weight, sign as i16, scale]
+            .iter()
+            .chain(digits)
+        {
+            bytes.extend(word.to_be_bytes());
+        }
+        return bytes;
+    }
+
+    #[test]
+    fn decodes_pg_uuid() {
+        let bytes = [
+            0x55, 0x0e, 0x84, 0x00, 0xe2, 0x9b, 0x41, 0xd4, 0xa7, 0x16, 0x44, 0x66, 0x55, 0x44,
+            0x00, 0x00,
+        ];
+        assert_eq!(
+            pg_binary_text("UUID", &bytes).as_deref(),
+            Some("550e8400-e29b-41d4-a716-446655440000")
+        );
+    }
+
+    #[test]
+    fn decodes_pg_numeric() {
+        let large = numeric(
+            7,
+            0,
+            5,
+            &[12, 3456, 7890, 1234, 5678, 9012, 3456, 7890, 1234, 5000],
+        );
+        assert_eq!(
+            pg_binary_text("NUMERIC", &large).as_deref(),
+            Some("123456789012345678901234567890.12345")
+        );
+        let small = numeric(-1, 0x4000, 4, &[12]);
+        assert_eq!(
+            pg_binary_text("NUMERIC", &small).as_deref(),
+            Some("-0.0012")
+        );
+        let round = numeric(1, 0, 0, &[1]);
+        assert_eq!(pg_binary_text("NUMERIC", &round).as_deref(), Some("10000"));
+        let nan = numeric(0, 0xC000, 0, &[]);
+        assert_eq!(pg_binary_text("NUMERIC", &nan).as_deref(), Some("NaN"));
+    }
+
+    #[test]
+    fn decodes_pg_array() {
+        let mut bytes = vec![];
+        for word in [1i32, 1, 25, 3, 1] {
+            bytes.extend(word.to_be_bytes());
+        }
+        for element in [Some("a b"), None, Some("c")] {
+            match element {
+                Some(text) => {
+                    bytes.extend((text.len() as i32).to_be_bytes());
+                    bytes.extend(text.as_bytes());
+                }
+                None => bytes.extend((-1i32).to_be_bytes()),
+            }
+        }
+        assert_eq!(
+            pg_binary_text("TEXT[]", &bytes).as_deref(),
+            Some("{\"a b\",NULL,c}")
+        );
     }
 }